@@ -2,6 +2,10 @@
 #![no_main]
 #![feature(naked_functions)]
 
+// Links in the crate's panic handler, since nothing else here uses the crate.
+#[cfg(feature = "panic_handler")]
+use gba_from_scratch as _;
+
 #[naked]
 #[no_mangle]
 #[instruction_set(arm::a32)]
@@ -20,6 +24,7 @@ unsafe extern "C" fn _start() -> ! {
   }
 }
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(_: &core::panic::PanicInfo) -> ! {
   loop {}
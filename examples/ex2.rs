@@ -12,6 +12,7 @@ pub extern "C" fn main() -> ! {
   }
 }
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(_: &core::panic::PanicInfo) -> ! {
   loop {}
@@ -2,8 +2,8 @@
 #![no_main]
 
 use gba_from_scratch::{
-  Color, DisplayControl, ObjAttr, BACKDROP, DISPCNT, OBJ_ATTRS, OBJ_PALETTE,
-  OBJ_TILE4,
+  Color, DisplayControl, ObjAttr, ObjSize, BACKDROP, DISPCNT, OBJ_ATTRS,
+  OBJ_PALETTE, OBJ_TILE4,
 };
 
 const JUST_OBJECTS_LINEAR: DisplayControl =
@@ -20,7 +20,8 @@ pub extern "C" fn main() -> ! {
   OBJ_TILE4.index(3).write(TILE_DOWN_LEFT);
   OBJ_TILE4.index(4).write(TILE_DOWN_RIGHT);
 
-  let obj = ObjAttr::new().with_size(1).with_tile(1).with_x(10).with_y(23);
+  let obj =
+    ObjAttr::new().with_size(ObjSize::Size1).with_tile(1).with_x(10).with_y(23);
   OBJ_ATTRS.index(0).write(obj);
 
   DISPCNT.write(JUST_OBJECTS_LINEAR);
@@ -28,6 +29,7 @@ pub extern "C" fn main() -> ! {
   loop {}
 }
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(_: &core::panic::PanicInfo) -> ! {
   loop {}
@@ -0,0 +1,1183 @@
+//! Wrappers for the GBA BIOS functions.
+//!
+//! Each function is called with a `swi` instruction. The number encoding of
+//! `swi` differs between ARM and Thumb code, so all the wrappers here are
+//! forced to be Thumb code with `#[instruction_set(arm::t32)]`. They can still
+//! be called from ARM code.
+//!
+//! The BIOS functions follow the C ABI, so the wrappers clobber `r0-r3`, `r12`
+//! and `lr`.
+//!
+//! The sound driver and music player functions (`swi 0x1A`-`0x1E`,
+//! `0x20`-`0x24`, and `0x28`-`0x2A`) take raw pointers to their work areas,
+//! since this crate doesn't model the Nintendo sound driver's data structures.
+//! For simple sound effects, see [`sound`](crate::sound) instead.
+
+use core::ffi::c_void;
+
+use bitfrob::{
+  u32_get_bit, u32_get_value, u32_with_bit, u32_with_value, u8_with_bit,
+};
+use voladdress::{Safe, VolBlock};
+
+use crate::{
+  compress::{Diff16Data, Diff8Data, HuffmanData, Lz77Data, RleData},
+  InterruptFlags,
+};
+
+/// Flags for which parts of the system [`register_ram_reset`] clears.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct RamResetFlags(u8);
+#[rustfmt::skip]
+impl RamResetFlags {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  #[inline]
+  pub const fn with_ewram(self, b: bool) -> Self { Self(u8_with_bit(0, self.0, b)) }
+  /// Clears IWRAM *except* the last `0x200` bytes (the BIOS stack area).
+  #[inline]
+  pub const fn with_iwram(self, b: bool) -> Self { Self(u8_with_bit(1, self.0, b)) }
+  #[inline]
+  pub const fn with_palram(self, b: bool) -> Self { Self(u8_with_bit(2, self.0, b)) }
+  #[inline]
+  pub const fn with_vram(self, b: bool) -> Self { Self(u8_with_bit(3, self.0, b)) }
+  #[inline]
+  pub const fn with_oam(self, b: bool) -> Self { Self(u8_with_bit(4, self.0, b)) }
+  #[inline]
+  pub const fn with_sio_registers(self, b: bool) -> Self { Self(u8_with_bit(5, self.0, b)) }
+  #[inline]
+  pub const fn with_sound_registers(self, b: bool) -> Self { Self(u8_with_bit(6, self.0, b)) }
+  #[inline]
+  pub const fn with_other_registers(self, b: bool) -> Self { Self(u8_with_bit(7, self.0, b)) }
+}
+
+/// The length and mode of a [`cpu_set`] or [`cpu_fast_set`] call.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct CpuSetControl(u32);
+impl CpuSetControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// The number of units (halfwords or words) to transfer.
+  ///
+  /// Only the low 21 bits are used. With [`cpu_fast_set`] this should be a
+  /// multiple of 8, otherwise it's rounded up.
+  #[inline]
+  pub const fn with_count(self, count: u32) -> Self {
+    Self(u32_with_value(0, 20, self.0, count))
+  }
+  #[inline]
+  pub const fn count(self) -> u32 {
+    u32_get_value(0, 20, self.0)
+  }
+  /// If the first source unit is repeated over the whole destination.
+  #[inline]
+  pub const fn with_fill(self, fill: bool) -> Self {
+    Self(u32_with_bit(24, self.0, fill))
+  }
+  #[inline]
+  pub const fn fill(self) -> bool {
+    u32_get_bit(24, self.0)
+  }
+  /// If [`cpu_set`] transfers words rather than halfwords.
+  ///
+  /// [`cpu_fast_set`] always transfers words.
+  #[inline]
+  pub const fn with_32bit(self, words: bool) -> Self {
+    Self(u32_with_bit(26, self.0, words))
+  }
+  #[inline]
+  pub const fn is_32bit(self) -> bool {
+    u32_get_bit(26, self.0)
+  }
+}
+
+/// Source data for [`bit_unpack`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct BitUnpackInfo {
+  /// Length of the source data, in bytes.
+  pub src_len: u16,
+  /// Bits per source unit: 1, 2, 4, or 8.
+  pub src_width: u8,
+  /// Bits per destination unit: 1, 2, 4, 8, 16, or 32.
+  pub dest_width: u8,
+  /// Bits 0-30 are added to each source unit. If bit 31 is set the offset is
+  /// also added to source units that are zero.
+  pub offset: u32,
+}
+
+/// Source data for [`bg_affine_set`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C, align(4))]
+pub struct BgAffineSource {
+  /// Texture space center, 20.8 fixed point.
+  pub tex_x: i32,
+  pub tex_y: i32,
+  /// Screen space center, in pixels.
+  pub scr_x: i16,
+  pub scr_y: i16,
+  /// Scale factors, 8.8 fixed point.
+  pub scale_x: i16,
+  pub scale_y: i16,
+  /// Rotation angle, where `0x1_0000` is a full turn. Only the upper 8 bits
+  /// are used.
+  pub angle: u16,
+}
+
+/// Output data of [`bg_affine_set`], in the layout of the `BG2PA`-`BG2Y`
+/// registers.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct BgAffineDest {
+  pub pa: i16,
+  pub pb: i16,
+  pub pc: i16,
+  pub pd: i16,
+  pub x: i32,
+  pub y: i32,
+}
+
+/// Source data for [`obj_affine_set`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C, align(4))]
+pub struct ObjAffineSource {
+  /// Scale factors, 8.8 fixed point.
+  pub scale_x: i16,
+  pub scale_y: i16,
+  /// Rotation angle, where `0x1_0000` is a full turn. Only the upper 8 bits
+  /// are used.
+  pub angle: u16,
+}
+
+/// `SoftReset` (`swi 0x00`): restarts the program.
+///
+/// Clears the top `0x200` bytes of IWRAM and then jumps to ROM (or to EWRAM,
+/// depending on the byte at `0x0300_7FFA`).
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn soft_reset() -> ! {
+  unsafe { core::arch::asm!("swi #0x00", options(noreturn)) }
+}
+
+/// `RegisterRamReset` (`swi 0x01`): clears the memory and registers selected.
+///
+/// ## Safety
+/// * Clearing IWRAM or EWRAM overwrites any Rust data stored there, so this
+///   should only be used before such data is in use (eg: at the very start of
+///   `main`).
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn register_ram_reset(flags: RamResetFlags) {
+  core::arch::asm!(
+    "swi #0x01",
+    inlateout("r0") flags.0 as u32 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `Halt` (`swi 0x02`): sleeps the CPU until any enabled interrupt fires.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn halt() {
+  unsafe { core::arch::asm!("swi #0x02", clobber_abi("C")) }
+}
+
+/// `Stop` (`swi 0x03`): very low power mode until a keypad, serial, or game
+/// pak interrupt fires.
+///
+/// The display should be blanked and sound turned off before calling this.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn stop() {
+  unsafe { core::arch::asm!("swi #0x03", clobber_abi("C")) }
+}
+
+/// `IntrWait` (`swi 0x04`): sleeps until one of the interrupts given fires.
+///
+/// Returns once one of the `flags` is set in [`INTR_CHECK`](crate::INTR_CHECK)
+/// (the master IRQ handler does this), clearing it. If `discard_old` is set
+/// the matching flags are cleared first, so only new interrupts count.
+///
+/// Interrupts must be enabled via `IME`, `IE`, and the device registers or
+/// this will never return.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn intr_wait(discard_old: bool, flags: InterruptFlags) {
+  unsafe {
+    core::arch::asm!(
+      "swi #0x04",
+      inlateout("r0") discard_old as u32 => _,
+      inlateout("r1") flags.0 as u32 => _,
+      clobber_abi("C"),
+    )
+  }
+}
+
+/// `VBlankIntrWait` (`swi 0x05`): sleeps until the next vertical blank
+/// interrupt.
+///
+/// Same as `intr_wait(true, InterruptFlags::new().with_vblank(true))`.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn vblank_intr_wait() {
+  unsafe { core::arch::asm!("swi #0x05", clobber_abi("C")) }
+}
+
+/// `Div` (`swi 0x06`): signed division, returns `(quotient, remainder)`.
+///
+/// ## Panics
+/// * If `denominator` is 0 (which would make the BIOS loop forever).
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn div(numerator: i32, denominator: i32) -> (i32, i32) {
+  assert!(denominator != 0);
+  let quotient: i32;
+  let remainder: i32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x06",
+      inlateout("r0") numerator => quotient,
+      inlateout("r1") denominator => remainder,
+      clobber_abi("C"),
+    )
+  }
+  (quotient, remainder)
+}
+
+/// `DivArm` (`swi 0x07`): the same as [`div`], but with the arguments in the
+/// other order (for compatibility with ARM's library).
+///
+/// ## Panics
+/// * If `denominator` is 0 (which would make the BIOS loop forever).
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn div_arm(denominator: i32, numerator: i32) -> (i32, i32) {
+  assert!(denominator != 0);
+  let quotient: i32;
+  let remainder: i32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x07",
+      inlateout("r0") denominator => quotient,
+      inlateout("r1") numerator => remainder,
+      clobber_abi("C"),
+    )
+  }
+  (quotient, remainder)
+}
+
+/// `Sqrt` (`swi 0x08`): integer square root.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn sqrt(x: u32) -> u16 {
+  let output: u32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x08",
+      inlateout("r0") x => output,
+      clobber_abi("C"),
+    )
+  }
+  output as u16
+}
+
+/// `ArcTan` (`swi 0x09`): arc tangent.
+///
+/// The input is a 1.14 fixed point tangent. The output angle is in the range
+/// `-0x4000..=0x4000`, where `0x1_0000` would be a full turn.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn arc_tan(tan: i16) -> i16 {
+  let output: i32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x09",
+      inlateout("r0") tan as i32 => output,
+      clobber_abi("C"),
+    )
+  }
+  output as i16
+}
+
+/// `ArcTan2` (`swi 0x0A`): the angle of the point `(x, y)`.
+///
+/// The output is in the range `0..=0xFFFF`, where `0x1_0000` would be a full
+/// turn.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn arc_tan2(x: i16, y: i16) -> u16 {
+  let output: u32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x0A",
+      inlateout("r0") x as i32 => output,
+      inlateout("r1") y as i32 => _,
+      clobber_abi("C"),
+    )
+  }
+  output as u16
+}
+
+/// `CpuSet` (`swi 0x0B`): copies or fills memory, by halfwords or words.
+///
+/// ## Safety
+/// * `src` must be readable for the whole transfer (or one unit, if filling).
+/// * `dest` must be writable for the whole transfer.
+/// * Both pointers must be aligned to the unit size.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn cpu_set(
+  src: *const c_void, dest: *mut c_void, control: CpuSetControl,
+) {
+  core::arch::asm!(
+    "swi #0x0B",
+    inlateout("r0") src => _,
+    inlateout("r1") dest => _,
+    inlateout("r2") control.0 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `CpuFastSet` (`swi 0x0C`): copies or fills memory by words, in blocks of 8
+/// words.
+///
+/// ## Safety
+/// * `src` must be readable for the whole transfer (or one word, if filling).
+/// * `dest` must be writable for the whole transfer, with the count rounded up
+///   to a multiple of 8.
+/// * Both pointers must be aligned to 4.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn cpu_fast_set(
+  src: *const u32, dest: *mut u32, control: CpuSetControl,
+) {
+  core::arch::asm!(
+    "swi #0x0C",
+    inlateout("r0") src => _,
+    inlateout("r1") dest => _,
+    inlateout("r2") control.0 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `GetBiosChecksum` (`swi 0x0D`).
+///
+/// Returns `0xBAAE187F` on a GBA, or `0xBAAE1880` on a DS.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn get_bios_checksum() -> u32 {
+  let output: u32;
+  unsafe {
+    core::arch::asm!(
+      "swi #0x0D",
+      lateout("r0") output,
+      clobber_abi("C"),
+    )
+  }
+  output
+}
+
+/// `BgAffineSet` (`swi 0x0E`): computes background affine parameters.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn bg_affine_set(src: &[BgAffineSource], dest: &mut [BgAffineDest]) {
+  let count = src.len().min(dest.len());
+  unsafe {
+    core::arch::asm!(
+      "swi #0x0E",
+      inlateout("r0") src.as_ptr() => _,
+      inlateout("r1") dest.as_mut_ptr() => _,
+      inlateout("r2") count => _,
+      clobber_abi("C"),
+    )
+  }
+}
+
+/// `ObjAffineSet` (`swi 0x0F`): computes object affine parameters.
+///
+/// Each output matrix is written as `pa, pb, pc, pd`, each value `stride`
+/// bytes apart. Use a stride of 2 to write into an `[i16; 4]`, or a stride
+/// of 8 to write directly into OAM.
+///
+/// ## Safety
+/// * `dest` must be writable for `count * 4` `i16` values spaced `stride` bytes
+///   apart.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn obj_affine_set(
+  src: &[ObjAffineSource], dest: *mut i16, stride: usize,
+) {
+  core::arch::asm!(
+    "swi #0x0F",
+    inlateout("r0") src.as_ptr() => _,
+    inlateout("r1") dest => _,
+    inlateout("r2") src.len() => _,
+    inlateout("r3") stride => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `BitUnPack` (`swi 0x10`): expands packed bit data into wider units.
+///
+/// ## Safety
+/// * `src` must be readable for `info.src_len` bytes.
+/// * `dest` must be aligned to 4 and writable for the full unpacked size
+///   (rounded up to a multiple of 4 bytes).
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn bit_unpack(src: *const u8, dest: *mut u32, info: &BitUnpackInfo) {
+  core::arch::asm!(
+    "swi #0x10",
+    inlateout("r0") src => _,
+    inlateout("r1") dest => _,
+    inlateout("r2") info => _,
+    clobber_abi("C"),
+  )
+}
+
+macro_rules! decompress_swi {
+  ($(#[$m:meta])* $name:ident = $swi:literal) => {
+    $(#[$m])*
+    #[inline]
+    #[instruction_set(arm::t32)]
+    unsafe fn $name(src: *const u32, dest: *mut u32) {
+      core::arch::asm!(
+        concat!("swi #", $swi),
+        inlateout("r0") src => _,
+        inlateout("r1") dest => _,
+        clobber_abi("C"),
+      )
+    }
+  };
+}
+
+decompress_swi!(
+  /// `LZ77UnCompReadNormalWrite8bit`
+  lz77_uncomp_write8 = "0x11"
+);
+decompress_swi!(
+  /// `LZ77UnCompReadNormalWrite16bit`
+  lz77_uncomp_write16 = "0x12"
+);
+decompress_swi!(
+  /// `HuffUnCompReadNormal`
+  huff_uncomp = "0x13"
+);
+decompress_swi!(
+  /// `RLUnCompReadNormalWrite8bit`
+  rl_uncomp_write8 = "0x14"
+);
+decompress_swi!(
+  /// `RLUnCompReadNormalWrite16bit`
+  rl_uncomp_write16 = "0x15"
+);
+decompress_swi!(
+  /// `Diff8bitUnFilterWrite8bit`
+  diff8_unfilter_write8 = "0x16"
+);
+decompress_swi!(
+  /// `Diff8bitUnFilterWrite16bit`
+  diff8_unfilter_write16 = "0x17"
+);
+decompress_swi!(
+  /// `Diff16bitUnFilter`
+  diff16_unfilter_raw = "0x18"
+);
+
+/// Checks the header of compressed data and returns the output size in words.
+///
+/// This only checks the header, the BIOS trusts the rest of the stream.
+///
+/// ## Panics
+/// * If `src` is empty, if the header's type byte isn't one of `kinds`, or if
+///   the output won't fit in `capacity` words.
+#[inline]
+fn checked_output_words(src: &[u32], kinds: &[u8], capacity: usize) -> usize {
+  let header = *src.first().expect("compressed data has no header");
+  assert!(kinds.contains(&(header as u8)), "wrong compression type");
+  let words = ((header >> 8) as usize + 3) / 4;
+  assert!(words <= capacity, "decompressed data is too large");
+  words
+}
+
+/// LZ77 decompresses `src` into `dest` (use for WRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn lz77_decompress<const N: usize>(src: &Lz77Data<N>, dest: &mut [u32]) {
+  unsafe { lz77_decompress_unchecked(src.as_words(), dest) }
+}
+
+/// LZ77 decompresses `src` into `dest` (use for WRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`lz77_compress`](crate::compress::lz77_compress) can use the safe
+/// [`lz77_decompress`] instead.
+///
+/// ## Panics
+/// * If `src` isn't LZ77 data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed LZ77 data, such as the output of
+///   [`lz77_compress`](crate::compress::lz77_compress). The BIOS trusts the
+///   stream past the header, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn lz77_decompress_unchecked(src: &[u32], dest: &mut [u32]) {
+  checked_output_words(src, &[0x10], dest.len());
+  unsafe { lz77_uncomp_write8(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// LZ77 decompresses `src` into `dest` using only 16-bit writes (use for VRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn lz77_decompress_vram<const N: usize, const C: usize>(
+  src: &Lz77Data<N>, dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  unsafe { lz77_decompress_vram_unchecked(src.as_words(), dest) }
+}
+
+/// LZ77 decompresses `src` into `dest` using only 16-bit writes (use for VRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`lz77_compress`](crate::compress::lz77_compress) can use the safe
+/// [`lz77_decompress_vram`] instead.
+///
+/// The data must not use a back reference distance of 1 (the output of
+/// [`lz77_compress`](crate::compress::lz77_compress) never does).
+///
+/// ## Panics
+/// * If `src` isn't LZ77 data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed LZ77 data, such as the output of
+///   [`lz77_compress`](crate::compress::lz77_compress). The BIOS trusts the
+///   stream past the header, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn lz77_decompress_vram_unchecked<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  checked_output_words(src, &[0x10], C);
+  unsafe { lz77_uncomp_write16(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Huffman decompresses `src` into `dest`.
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn huffman_decompress<const N: usize>(
+  src: &HuffmanData<N>, dest: &mut [u32],
+) {
+  unsafe { huffman_decompress_unchecked(src.as_words(), dest) }
+}
+
+/// Huffman decompresses `src` into `dest`.
+///
+/// Use this for data from other sources. Data made by
+/// [`huffman4_compress`](crate::compress::huffman4_compress) can use the safe
+/// [`huffman_decompress`] instead.
+///
+/// ## Panics
+/// * If `src` isn't Huffman data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed Huffman data, such as the output of
+///   [`huffman4_compress`](crate::compress::huffman4_compress). The BIOS trusts
+///   the tree and bitstream, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn huffman_decompress_unchecked(src: &[u32], dest: &mut [u32]) {
+  checked_output_words(src, &[0x24, 0x28], dest.len());
+  unsafe { huff_uncomp(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Huffman decompresses `src` into `dest`.
+///
+/// The BIOS always writes Huffman output in 32-bit units, so this is safe
+/// for VRAM.
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn huffman_decompress_vram<const N: usize, const C: usize>(
+  src: &HuffmanData<N>, dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  unsafe { huffman_decompress_vram_unchecked(src.as_words(), dest) }
+}
+
+/// Huffman decompresses `src` into `dest`.
+///
+/// Use this for data from other sources. Data made by
+/// [`huffman4_compress`](crate::compress::huffman4_compress) can use the safe
+/// [`huffman_decompress_vram`] instead.
+///
+/// The BIOS always writes Huffman output in 32-bit units, so this is safe
+/// for VRAM.
+///
+/// ## Panics
+/// * If `src` isn't Huffman data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed Huffman data, such as the output of
+///   [`huffman4_compress`](crate::compress::huffman4_compress). The BIOS trusts
+///   the tree and bitstream, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn huffman_decompress_vram_unchecked<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  checked_output_words(src, &[0x24, 0x28], C);
+  unsafe { huff_uncomp(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Run-length decompresses `src` into `dest` (use for WRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn rle_decompress<const N: usize>(src: &RleData<N>, dest: &mut [u32]) {
+  unsafe { rle_decompress_unchecked(src.as_words(), dest) }
+}
+
+/// Run-length decompresses `src` into `dest` (use for WRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`rle_compress`](crate::compress::rle_compress) can use the safe
+/// [`rle_decompress`] instead.
+///
+/// ## Panics
+/// * If `src` isn't RLE data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed RLE data, such as the output of
+///   [`rle_compress`](crate::compress::rle_compress). The BIOS trusts the
+///   stream past the header, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn rle_decompress_unchecked(src: &[u32], dest: &mut [u32]) {
+  checked_output_words(src, &[0x30], dest.len());
+  unsafe { rl_uncomp_write8(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Run-length decompresses `src` into `dest` using only 16-bit writes (use
+/// for VRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn rle_decompress_vram<const N: usize, const C: usize>(
+  src: &RleData<N>, dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  unsafe { rle_decompress_vram_unchecked(src.as_words(), dest) }
+}
+
+/// Run-length decompresses `src` into `dest` using only 16-bit writes (use
+/// for VRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`rle_compress`](crate::compress::rle_compress) can use the safe
+/// [`rle_decompress_vram`] instead.
+///
+/// ## Panics
+/// * If `src` isn't RLE data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must be complete, well-formed RLE data, such as the output of
+///   [`rle_compress`](crate::compress::rle_compress). The BIOS trusts the
+///   stream past the header, so bad data can read past the end of `src` or
+///   write past the end of `dest`.
+#[inline]
+pub unsafe fn rle_decompress_vram_unchecked<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  checked_output_words(src, &[0x30], C);
+  unsafe { rl_uncomp_write16(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Undoes an 8-bit difference filter from `src` into `dest` (use for WRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn diff8_unfilter<const N: usize>(src: &Diff8Data<N>, dest: &mut [u32]) {
+  unsafe { diff8_unfilter_unchecked(src.as_words(), dest) }
+}
+
+/// Undoes an 8-bit difference filter from `src` into `dest` (use for WRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`diff8_filter`](crate::compress::diff8_filter) can use the safe
+/// [`diff8_unfilter`] instead.
+///
+/// ## Panics
+/// * If `src` isn't diff filtered data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must hold all of the data its header claims, such as the output of
+///   [`diff8_filter`](crate::compress::diff8_filter). Otherwise the BIOS reads
+///   past the end of `src`.
+#[inline]
+pub unsafe fn diff8_unfilter_unchecked(src: &[u32], dest: &mut [u32]) {
+  checked_output_words(src, &[0x81], dest.len());
+  unsafe { diff8_unfilter_write8(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Undoes an 8-bit difference filter from `src` into `dest` using only 16-bit
+/// writes (use for VRAM).
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn diff8_unfilter_vram<const N: usize, const C: usize>(
+  src: &Diff8Data<N>, dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  unsafe { diff8_unfilter_vram_unchecked(src.as_words(), dest) }
+}
+
+/// Undoes an 8-bit difference filter from `src` into `dest` using only 16-bit
+/// writes (use for VRAM).
+///
+/// Use this for data from other sources. Data made by
+/// [`diff8_filter`](crate::compress::diff8_filter) can use the safe
+/// [`diff8_unfilter_vram`] instead.
+///
+/// ## Panics
+/// * If `src` isn't diff filtered data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must hold all of the data its header claims, such as the output of
+///   [`diff8_filter`](crate::compress::diff8_filter). Otherwise the BIOS reads
+///   past the end of `src`.
+#[inline]
+pub unsafe fn diff8_unfilter_vram_unchecked<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  checked_output_words(src, &[0x81], C);
+  unsafe { diff8_unfilter_write16(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Undoes a 16-bit difference filter from `src` into `dest`.
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn diff16_unfilter<const N: usize>(src: &Diff16Data<N>, dest: &mut [u32]) {
+  unsafe { diff16_unfilter_unchecked(src.as_words(), dest) }
+}
+
+/// Undoes a 16-bit difference filter from `src` into `dest`.
+///
+/// Use this for data from other sources. Data made by
+/// [`diff16_filter`](crate::compress::diff16_filter) can use the safe
+/// [`diff16_unfilter`] instead.
+///
+/// ## Panics
+/// * If `src` isn't diff filtered data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must hold all of the data its header claims, such as the output of
+///   [`diff16_filter`](crate::compress::diff16_filter). Otherwise the BIOS
+///   reads past the end of `src`.
+#[inline]
+pub unsafe fn diff16_unfilter_unchecked(src: &[u32], dest: &mut [u32]) {
+  checked_output_words(src, &[0x82], dest.len());
+  unsafe { diff16_unfilter_raw(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// Undoes a 16-bit difference filter from `src` into `dest`.
+///
+/// The BIOS writes this output in 16-bit units, so this is safe for VRAM.
+///
+/// ## Panics
+/// * If the output won't fit into `dest`.
+#[inline]
+pub fn diff16_unfilter_vram<const N: usize, const C: usize>(
+  src: &Diff16Data<N>, dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  unsafe { diff16_unfilter_vram_unchecked(src.as_words(), dest) }
+}
+
+/// Undoes a 16-bit difference filter from `src` into `dest`.
+///
+/// Use this for data from other sources. Data made by
+/// [`diff16_filter`](crate::compress::diff16_filter) can use the safe
+/// [`diff16_unfilter_vram`] instead.
+///
+/// The BIOS writes this output in 16-bit units, so this is safe for VRAM.
+///
+/// ## Panics
+/// * If `src` isn't diff filtered data, or if it won't fit into `dest`.
+///
+/// ## Safety
+/// * `src` must hold all of the data its header claims, such as the output of
+///   [`diff16_filter`](crate::compress::diff16_filter). Otherwise the BIOS
+///   reads past the end of `src`.
+#[inline]
+pub unsafe fn diff16_unfilter_vram_unchecked<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  checked_output_words(src, &[0x82], C);
+  unsafe { diff16_unfilter_raw(src.as_ptr(), dest.as_mut_ptr()) }
+}
+
+/// `SoundBias` (`swi 0x19`): smoothly moves `SOUNDBIAS` to `0x000` (if
+/// `level` is 0) or `0x200` (otherwise).
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn sound_bias(level: u32) {
+  unsafe {
+    core::arch::asm!(
+      "swi #0x19",
+      inlateout("r0") level => _,
+      clobber_abi("C"),
+    )
+  }
+}
+
+/// `SoundDriverInit` (`swi 0x1A`): sets up the BIOS sound driver.
+///
+/// The address of `area` is stored at `0x0300_7FF0` for the other sound driver
+/// functions to use.
+///
+/// ## Safety
+/// * `area` must point to a `SoundArea` work area (`0xFB0` bytes, 4-aligned)
+///   that stays valid for as long as the sound driver is in use.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_init(area: *mut c_void) {
+  core::arch::asm!(
+    "swi #0x1A",
+    inlateout("r0") area => _,
+    clobber_abi("C"),
+  )
+}
+
+/// Settings for [`sound_driver_mode`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct SoundDriverMode(u32);
+impl SoundDriverMode {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// Direct Sound reverb, `0..=127`. Only applied if
+  /// [`with_set_reverb`](Self::with_set_reverb) is also set.
+  #[inline]
+  pub const fn with_reverb(self, reverb: u32) -> Self {
+    Self(u32_with_value(0, 6, self.0, reverb))
+  }
+  #[inline]
+  pub const fn reverb(self) -> u32 {
+    u32_get_value(0, 6, self.0)
+  }
+  #[inline]
+  pub const fn with_set_reverb(self, set: bool) -> Self {
+    Self(u32_with_bit(7, self.0, set))
+  }
+  #[inline]
+  pub const fn set_reverb(self) -> bool {
+    u32_get_bit(7, self.0)
+  }
+  /// How many channels can play at once, `1..=12`.
+  #[inline]
+  pub const fn with_channels(self, channels: u32) -> Self {
+    Self(u32_with_value(8, 11, self.0, channels))
+  }
+  #[inline]
+  pub const fn channels(self) -> u32 {
+    u32_get_value(8, 11, self.0)
+  }
+  /// Master volume, `1..=15`.
+  #[inline]
+  pub const fn with_volume(self, volume: u32) -> Self {
+    Self(u32_with_value(12, 15, self.0, volume))
+  }
+  #[inline]
+  pub const fn volume(self) -> u32 {
+    u32_get_value(12, 15, self.0)
+  }
+  /// Playback rate, `1..=12` (5734 Hz up to 42048 Hz, see GBATEK).
+  #[inline]
+  pub const fn with_frequency(self, frequency: u32) -> Self {
+    Self(u32_with_value(16, 19, self.0, frequency))
+  }
+  #[inline]
+  pub const fn frequency(self) -> u32 {
+    u32_get_value(16, 19, self.0)
+  }
+  /// Output resolution, `8..=11` for 9-bit down to 6-bit.
+  #[inline]
+  pub const fn with_da_bits(self, da_bits: u32) -> Self {
+    Self(u32_with_value(20, 23, self.0, da_bits))
+  }
+  #[inline]
+  pub const fn da_bits(self) -> u32 {
+    u32_get_value(20, 23, self.0)
+  }
+}
+
+/// `SoundDriverMode` (`swi 0x1B`): changes the sound driver's settings.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_mode(mode: SoundDriverMode) {
+  core::arch::asm!(
+    "swi #0x1B",
+    inlateout("r0") mode.0 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `SoundDriverMain` (`swi 0x1C`): mixes the next frame of sound.
+///
+/// Call this once per frame, soon after [`sound_driver_vsync`].
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_main() {
+  core::arch::asm!("swi #0x1C", clobber_abi("C"))
+}
+
+/// `SoundDriverVSync` (`swi 0x1D`): resets the sound DMA. Call this right
+/// at the start of every VBlank.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_vsync() {
+  core::arch::asm!("swi #0x1D", clobber_abi("C"))
+}
+
+/// `SoundChannelClear` (`swi 0x1E`): stops every Direct Sound channel of the
+/// sound driver.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_channel_clear() {
+  core::arch::asm!("swi #0x1E", clobber_abi("C"))
+}
+
+/// `MidiKey2Freq` (`swi 0x1F`): the sample rate to play a sound at for a
+/// given MIDI key.
+///
+/// `key` is the MIDI key (`0..=178`) and `fine` is a fine adjustment in 256ths
+/// of a semitone. The result is in the same units as the wave's own frequency
+/// value.
+///
+/// ## Safety
+/// * `wave` must point to a BIOS `WaveData` header, which has the base
+///   frequency (for MIDI key 60) as a `u32` at offset 4.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn midi_key_to_freq(wave: *const c_void, key: u8, fine: u8) -> u32 {
+  let output: u32;
+  core::arch::asm!(
+    "swi #0x1F",
+    inlateout("r0") wave => output,
+    inlateout("r1") key as u32 => _,
+    inlateout("r2") fine as u32 => _,
+    clobber_abi("C"),
+  );
+  output
+}
+
+/// `MusicPlayerOpen` (`swi 0x20`): sets up a music player with `tracks`
+/// track work areas.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+/// * `player` must point to a `MusicPlayerInfo` work area, and `track` to
+///   `tracks` `MusicPlayerTrack` work areas. Both must stay valid for as long
+///   as the player is in use.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn music_player_open(
+  player: *mut c_void, track: *mut c_void, tracks: u8,
+) {
+  core::arch::asm!(
+    "swi #0x20",
+    inlateout("r0") player => _,
+    inlateout("r1") track => _,
+    inlateout("r2") tracks as u32 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `MusicPlayerStart` (`swi 0x21`): starts playing `song` on a music player.
+///
+/// ## Safety
+/// * `player` must have been set up with [`music_player_open`].
+/// * `song` must point to a `SongHeader` and its track data, which must stay
+///   valid while the song plays.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn music_player_start(player: *mut c_void, song: *const c_void) {
+  core::arch::asm!(
+    "swi #0x21",
+    inlateout("r0") player => _,
+    inlateout("r1") song => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `MusicPlayerStop` (`swi 0x22`): pauses a music player.
+///
+/// ## Safety
+/// * `player` must have been set up with [`music_player_open`].
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn music_player_stop(player: *mut c_void) {
+  core::arch::asm!(
+    "swi #0x22",
+    inlateout("r0") player => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `MusicPlayerContinue` (`swi 0x23`): resumes a music player paused by
+/// [`music_player_stop`].
+///
+/// ## Safety
+/// * `player` must have been set up with [`music_player_open`].
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn music_player_continue(player: *mut c_void) {
+  core::arch::asm!(
+    "swi #0x23",
+    inlateout("r0") player => _,
+    clobber_abi("C"),
+  )
+}
+
+/// `MusicPlayerFadeOut` (`swi 0x24`): fades a music player out over time,
+/// then stops it. Larger `speed` values fade more slowly.
+///
+/// ## Safety
+/// * `player` must have been set up with [`music_player_open`].
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn music_player_fade_out(player: *mut c_void, speed: u16) {
+  core::arch::asm!(
+    "swi #0x24",
+    inlateout("r0") player => _,
+    inlateout("r1") speed as u32 => _,
+    clobber_abi("C"),
+  )
+}
+
+/// The serial transfer mode used by [`multi_boot`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u32)]
+pub enum MultiBootMode {
+  /// 256 KHz, 32-bit Normal mode: fast and stable, one client.
+  Normal256Khz = 0,
+  /// 115 KHz, 16-bit Multi-Play mode: slow, up to three clients.
+  #[default]
+  MultiPlay = 1,
+  /// 2 MHz, 32-bit Normal mode: fastest, but unstable.
+  Normal2Mhz = 2,
+}
+
+/// `MultiBoot` (`swi 0x25`): sends a program to other GBAs over the link
+/// cable. Returns `true` if the transfer worked.
+///
+/// ## Safety
+/// * `param` must point to a `MultiBootParam` structure (see GBATEK) that has
+///   been filled in from the client handshake, and that points to the program
+///   to send.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn multi_boot(param: *const c_void, mode: MultiBootMode) -> bool {
+  let output: u32;
+  core::arch::asm!(
+    "swi #0x25",
+    inlateout("r0") param => output,
+    inlateout("r1") mode as u32 => _,
+    clobber_abi("C"),
+  );
+  output == 0
+}
+
+/// `HardReset` (`swi 0x26`): restarts the GBA from the boot logo.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn hard_reset() -> ! {
+  unsafe { core::arch::asm!("swi #0x26", options(noreturn)) }
+}
+
+/// `CustomHalt` (`swi 0x27`): writes to `HALTCNT`, giving [`stop`] if `stop`
+/// is set, or [`halt`] otherwise.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn custom_halt(stop: bool) {
+  let haltcnt: u32 = if stop { 0x80 } else { 0x00 };
+  unsafe {
+    core::arch::asm!(
+      "swi #0x27",
+      inlateout("r2") haltcnt => _,
+      clobber_abi("C"),
+    )
+  }
+}
+
+/// `SoundDriverVSyncOff` (`swi 0x28`): stops the sound DMA, such as before
+/// turning off interrupts for a long time. The sound driver keeps its state.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_vsync_off() {
+  core::arch::asm!("swi #0x28", clobber_abi("C"))
+}
+
+/// `SoundDriverVSyncOn` (`swi 0x29`): restarts the sound DMA after
+/// [`sound_driver_vsync_off`]. Call this right at the start of a VBlank.
+///
+/// ## Safety
+/// * [`sound_driver_init`] must have been called, and its work area must still
+///   be valid.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_driver_vsync_on() {
+  core::arch::asm!("swi #0x29", clobber_abi("C"))
+}
+
+/// `SoundGetJumpList` (`swi 0x2A`): copies the addresses of the BIOS's
+/// internal sound functions into `dest`.
+///
+/// ## Safety
+/// * `dest` must be valid to write `0x120` bytes to (the buffer size GBATEK
+///   gives).
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn sound_get_jump_list(dest: *mut [u32; 72]) {
+  core::arch::asm!(
+    "swi #0x2A",
+    inlateout("r0") dest => _,
+    clobber_abi("C"),
+  )
+}
@@ -0,0 +1,521 @@
+//! Compressors for the formats that the BIOS can decompress.
+//!
+//! These are all `const fn`, so data can be compressed on the host while
+//! building and then stored in ROM:
+//!
+//! ```ignore
+//! use gba_from_scratch::compress::{
+//!   lz77_compress, lz77_compressed_len, Lz77Data,
+//! };
+//!
+//! const TILES: [u32; 16] = [0x11111111; 16];
+//! static TILES_LZ77: Lz77Data<{ lz77_compressed_len(&TILES) }> =
+//!   lz77_compress(&TILES);
+//! ```
+//!
+//! Data is given and returned as `u32` words, which are read as little-endian
+//! bytes (the same as how they are in memory). Each `_len` function gives the
+//! size of the output in words, and the matching compression function panics
+//! if it's asked for a different size.
+//!
+//! The output includes the 4 byte header that the BIOS expects, and is always
+//! padded to a whole number of words. Each format has its own wrapper type,
+//! which only the compressors here can make. Since the data inside is known to
+//! be well-formed, the BIOS can safely decompress it (eg: with
+//! [`lz77_decompress`](crate::bios::lz77_decompress)).
+//!
+//! Const eval is slow, so compressing a lot of data adds to build times: LZ77
+//! compressing a whole charblock (16 KiB) takes several seconds.
+
+/// Gets byte `i` of `src`.
+const fn byte_at(src: &[u32], i: usize) -> u8 {
+  (src[i / 4] >> (8 * (i % 4))) as u8
+}
+
+/// Gets 4-bit unit `i` of `src`, low nibble first.
+const fn nibble_at(src: &[u32], i: usize) -> u8 {
+  (byte_at(src, i / 2) >> (4 * (i % 2))) & 0xF
+}
+
+/// Ors a byte into byte position `i` of `out`, if that's in bounds.
+///
+/// Running a compressor with an output of length 0 just counts the size.
+macro_rules! or_byte {
+  ($out:ident, $i:expr, $b:expr) => {{
+    let i: usize = $i;
+    if i / 4 < $out.len() {
+      $out[i / 4] |= ($b as u32) << (8 * (i % 4));
+    }
+  }};
+}
+
+/// Declares a wrapper for the output of one of the compressors.
+macro_rules! compressed_data {
+  ($(#[$m:meta])* $name:ident) => {
+    $(#[$m])*
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    pub struct $name<const N: usize>([u32; N]);
+    impl<const N: usize> $name<N> {
+      /// The data as words, starting with the header.
+      #[inline]
+      pub const fn as_words(&self) -> &[u32; N] {
+        &self.0
+      }
+    }
+  };
+}
+
+compressed_data!(
+  /// LZ77 data made by [`lz77_compress`].
+  Lz77Data
+);
+compressed_data!(
+  /// Run-length encoded data made by [`rle_compress`].
+  RleData
+);
+compressed_data!(
+  /// Huffman data made by [`huffman4_compress`].
+  HuffmanData
+);
+compressed_data!(
+  /// 8-bit difference filtered data made by [`diff8_filter`].
+  Diff8Data
+);
+compressed_data!(
+  /// 16-bit difference filtered data made by [`diff16_filter`].
+  Diff16Data
+);
+
+/// The compression header: a type byte, then the uncompressed size in bytes.
+const fn header(kind: u32, src: &[u32]) -> u32 {
+  kind | ((src.len() as u32 * 4) << 8)
+}
+
+/// The LZ77 window size, which is also the size of the match finder's tables.
+const LZ77_WINDOW: usize = 4096;
+
+/// How many earlier positions the match finder tries before giving up.
+const LZ77_MAX_CHAIN: usize = 32;
+
+/// Hashes the 3 bytes starting at `pos`, for the LZ77 match finder.
+const fn lz77_hash(src: &[u32], pos: usize) -> usize {
+  let bytes = byte_at(src, pos) as u32
+    | (byte_at(src, pos + 1) as u32) << 8
+    | (byte_at(src, pos + 2) as u32) << 16;
+  (bytes.wrapping_mul(0x9E37_79B1) >> 20) as usize
+}
+
+/// LZ77 with a hash chain match finder.
+///
+/// `head` holds the latest position (plus 1, so 0 is empty) with each hash,
+/// and `prev` holds the position before that with the same hash, for each
+/// position in the window (indexed by position modulo the window size).
+/// Searching only those positions keeps this fast enough for const eval, at the
+/// cost of sometimes missing the best match.
+const fn lz77<const N: usize>(src: &[u32]) -> ([u32; N], usize) {
+  let size = src.len() * 4;
+  let mut out = [0_u32; N];
+  if N > 0 {
+    out[0] = header(0x10, src);
+  }
+  let mut head = [0_u32; LZ77_WINDOW];
+  let mut prev = [0_u32; LZ77_WINDOW];
+  let mut len: usize = 4;
+  let mut pos: usize = 0;
+  let mut next_insert: usize = 0;
+  while pos < size {
+    let flags_at = len;
+    len += 1;
+    let mut block: usize = 0;
+    while block < 8 && pos < size {
+      let max_len = if size - pos < 18 { size - pos } else { 18 };
+      let mut best_len: usize = 0;
+      let mut best_disp: usize = 0;
+      if max_len >= 3 {
+        let mut cand = head[lz77_hash(src, pos)] as usize;
+        let mut chain = 0;
+        while cand != 0 && chain < LZ77_MAX_CHAIN {
+          let start = cand - 1;
+          let disp = pos - start;
+          if disp > LZ77_WINDOW {
+            break;
+          }
+          // A distance of 1 would break the BIOS's 16-bit output mode.
+          if disp >= 2 {
+            let mut l = 0;
+            while l < max_len
+              && byte_at(src, pos + l) == byte_at(src, start + l)
+            {
+              l += 1;
+            }
+            if l > best_len {
+              best_len = l;
+              best_disp = disp;
+              if l == max_len {
+                break;
+              }
+            }
+          }
+          cand = prev[start % LZ77_WINDOW] as usize;
+          chain += 1;
+        }
+      }
+      if best_len >= 3 {
+        let d = best_disp - 1;
+        or_byte!(out, flags_at, 0x80_usize >> block);
+        or_byte!(out, len, ((best_len - 3) << 4) | (d >> 8));
+        or_byte!(out, len + 1, d & 0xFF);
+        len += 2;
+        pos += best_len;
+      } else {
+        or_byte!(out, len, byte_at(src, pos));
+        len += 1;
+        pos += 1;
+      }
+      // Add every position that was passed over to the hash chains.
+      while next_insert < pos && next_insert + 3 <= size {
+        let h = lz77_hash(src, next_insert);
+        prev[next_insert % LZ77_WINDOW] = head[h];
+        head[h] = next_insert as u32 + 1;
+        next_insert += 1;
+      }
+      block += 1;
+    }
+  }
+  (out, (len + 3) / 4)
+}
+
+/// The size, in words, of the LZ77 compressed form of `src`.
+pub const fn lz77_compressed_len(src: &[u32]) -> usize {
+  lz77::<0>(src).1
+}
+
+/// LZ77 compresses `src`, for use with
+/// [`lz77_decompress`](crate::bios::lz77_decompress) or
+/// [`lz77_decompress_vram`](crate::bios::lz77_decompress_vram).
+///
+/// ## Panics
+/// * If `N` isn't `lz77_compressed_len(src)`.
+pub const fn lz77_compress<const N: usize>(src: &[u32]) -> Lz77Data<N> {
+  let (out, len) = lz77::<N>(src);
+  assert!(len == N, "`N` must be `lz77_compressed_len(src)`");
+  Lz77Data(out)
+}
+
+/// How many times the byte at `pos` repeats, up to the RLE max of 130.
+const fn run_length(src: &[u32], pos: usize) -> usize {
+  let size = src.len() * 4;
+  let b = byte_at(src, pos);
+  let mut n = 1;
+  while n < 130 && pos + n < size && byte_at(src, pos + n) == b {
+    n += 1;
+  }
+  n
+}
+
+const fn rle<const N: usize>(src: &[u32]) -> ([u32; N], usize) {
+  let size = src.len() * 4;
+  let mut out = [0_u32; N];
+  if N > 0 {
+    out[0] = header(0x30, src);
+  }
+  let mut len: usize = 4;
+  let mut pos: usize = 0;
+  while pos < size {
+    let run = run_length(src, pos);
+    if run >= 3 {
+      or_byte!(out, len, 0x80 | (run - 3));
+      or_byte!(out, len + 1, byte_at(src, pos));
+      len += 2;
+      pos += run;
+    } else {
+      let start = pos;
+      while pos < size && pos - start < 128 && run_length(src, pos) < 3 {
+        pos += 1;
+      }
+      or_byte!(out, len, pos - start - 1);
+      len += 1;
+      let mut i = start;
+      while i < pos {
+        or_byte!(out, len, byte_at(src, i));
+        len += 1;
+        i += 1;
+      }
+    }
+  }
+  (out, (len + 3) / 4)
+}
+
+/// The size, in words, of the run-length compressed form of `src`.
+pub const fn rle_compressed_len(src: &[u32]) -> usize {
+  rle::<0>(src).1
+}
+
+/// Run-length compresses `src`, for use with
+/// [`rle_decompress`](crate::bios::rle_decompress) or
+/// [`rle_decompress_vram`](crate::bios::rle_decompress_vram).
+///
+/// ## Panics
+/// * If `N` isn't `rle_compressed_len(src)`.
+pub const fn rle_compress<const N: usize>(src: &[u32]) -> RleData<N> {
+  let (out, len) = rle::<N>(src);
+  assert!(len == N, "`N` must be `rle_compressed_len(src)`");
+  RleData(out)
+}
+
+const fn huffman4<const N: usize>(src: &[u32]) -> ([u32; N], usize) {
+  let size = src.len() * 4;
+  let mut out = [0_u32; N];
+  if N > 0 {
+    out[0] = header(0x24, src);
+  }
+
+  // Nodes `0..16` are the leaves (one per symbol), and nodes `16..31` are the
+  // internal nodes as they get made.
+  let mut weight = [0_usize; 31];
+  let mut alive = [false; 31];
+  let mut i = 0;
+  while i < size * 2 {
+    let s = nibble_at(src, i) as usize;
+    weight[s] += 1;
+    alive[s] = true;
+    i += 1;
+  }
+  // The BIOS needs the tree to have at least two leaves.
+  let mut leaves = 0;
+  let mut s = 0;
+  while s < 16 {
+    if alive[s] {
+      leaves += 1;
+    }
+    s += 1;
+  }
+  s = 0;
+  while leaves < 2 {
+    if !alive[s] {
+      alive[s] = true;
+      leaves += 1;
+    }
+    s += 1;
+  }
+
+  // Build the tree by joining the two lightest nodes until one is left.
+  let mut left = [0_usize; 31];
+  let mut right = [0_usize; 31];
+  let mut parent = [0_usize; 31];
+  let mut is_right = [false; 31];
+  let mut next = 16;
+  let mut remaining = leaves;
+  while remaining > 1 {
+    let mut pick = [0_usize; 2];
+    let mut p = 0;
+    while p < 2 {
+      let mut best = usize::MAX;
+      let mut n = 0;
+      while n < next {
+        if alive[n] && (best == usize::MAX || weight[n] < weight[best]) {
+          best = n;
+        }
+        n += 1;
+      }
+      alive[best] = false;
+      pick[p] = best;
+      p += 1;
+    }
+    left[next] = pick[0];
+    right[next] = pick[1];
+    parent[pick[0]] = next;
+    parent[pick[1]] = next;
+    is_right[pick[1]] = true;
+    weight[next] = weight[pick[0]] + weight[pick[1]];
+    alive[next] = true;
+    next += 1;
+    remaining -= 1;
+  }
+  let root = next - 1;
+
+  // Write the tree table breadth first. Table position 0 is the tree size
+  // byte and position 1 is the root, then each node's children are a pair.
+  let mut queue = [0_usize; 16];
+  let mut queue_at = [0_usize; 16];
+  let mut head = 0;
+  let mut tail = 1;
+  queue[0] = root;
+  queue_at[0] = 1;
+  let mut next_pair: usize = 2;
+  while head < tail {
+    let n = queue[head];
+    let at = queue_at[head];
+    head += 1;
+    let pair = next_pair;
+    next_pair += 2;
+    let mut node = (pair - (at & !1) - 2) / 2;
+    if left[n] < 16 {
+      node |= 0x80;
+      or_byte!(out, 4 + pair, left[n]);
+    } else {
+      queue[tail] = left[n];
+      queue_at[tail] = pair;
+      tail += 1;
+    }
+    if right[n] < 16 {
+      node |= 0x40;
+      or_byte!(out, 4 + pair + 1, right[n]);
+    } else {
+      queue[tail] = right[n];
+      queue_at[tail] = pair + 1;
+      tail += 1;
+    }
+    or_byte!(out, 4 + at, node);
+  }
+  let table_len = (next_pair + 3) / 4 * 4;
+  or_byte!(out, 4, table_len / 2 - 1);
+
+  // Write the bitstream, first bit in bit 31 of each word.
+  let mut len = 4 + table_len;
+  let mut word = 0_u32;
+  let mut bits = 0;
+  i = 0;
+  while i < size * 2 {
+    let s = nibble_at(src, i) as usize;
+    // The path from the leaf up to the root, so it's written in reverse.
+    let mut path = 0_u32;
+    let mut depth = 0;
+    let mut n = s;
+    while n != root {
+      path |= (is_right[n] as u32) << depth;
+      depth += 1;
+      n = parent[n];
+    }
+    while depth > 0 {
+      depth -= 1;
+      word |= ((path >> depth) & 1) << (31 - bits);
+      bits += 1;
+      if bits == 32 {
+        if len / 4 < N {
+          out[len / 4] = word;
+        }
+        len += 4;
+        word = 0;
+        bits = 0;
+      }
+    }
+    i += 1;
+  }
+  if bits > 0 {
+    if len / 4 < N {
+      out[len / 4] = word;
+    }
+    len += 4;
+  }
+  (out, len / 4)
+}
+
+/// The size, in words, of the 4-bit Huffman compressed form of `src`.
+pub const fn huffman4_compressed_len(src: &[u32]) -> usize {
+  huffman4::<0>(src).1
+}
+
+/// Huffman compresses `src` using 4-bit units, for use with
+/// [`huffman_decompress`](crate::bios::huffman_decompress) or
+/// [`huffman_decompress_vram`](crate::bios::huffman_decompress_vram).
+///
+/// ## Panics
+/// * If `N` isn't `huffman4_compressed_len(src)`.
+pub const fn huffman4_compress<const N: usize>(src: &[u32]) -> HuffmanData<N> {
+  let (out, len) = huffman4::<N>(src);
+  assert!(len == N, "`N` must be `huffman4_compressed_len(src)`");
+  HuffmanData(out)
+}
+
+/// The size, in words, of the diff filtered form of `src`.
+///
+/// This is always one more than the length of `src`.
+pub const fn diff_filtered_len(src: &[u32]) -> usize {
+  src.len() + 1
+}
+
+/// Applies an 8-bit difference filter to `src`, for use with
+/// [`diff8_unfilter`](crate::bios::diff8_unfilter) or
+/// [`diff8_unfilter_vram`](crate::bios::diff8_unfilter_vram).
+///
+/// ## Panics
+/// * If `N` isn't `diff_filtered_len(src)`.
+pub const fn diff8_filter<const N: usize>(src: &[u32]) -> Diff8Data<N> {
+  assert!(N == diff_filtered_len(src), "`N` must be `diff_filtered_len(src)`");
+  let mut out = [0_u32; N];
+  out[0] = header(0x81, src);
+  let mut prev = 0_u8;
+  let mut i = 0;
+  while i < src.len() * 4 {
+    let b = byte_at(src, i);
+    or_byte!(out, 4 + i, b.wrapping_sub(prev));
+    prev = b;
+    i += 1;
+  }
+  Diff8Data(out)
+}
+
+/// Applies a 16-bit difference filter to `src`, for use with
+/// [`diff16_unfilter`](crate::bios::diff16_unfilter) or
+/// [`diff16_unfilter_vram`](crate::bios::diff16_unfilter_vram).
+///
+/// ## Panics
+/// * If `N` isn't `diff_filtered_len(src)`.
+pub const fn diff16_filter<const N: usize>(src: &[u32]) -> Diff16Data<N> {
+  assert!(N == diff_filtered_len(src), "`N` must be `diff_filtered_len(src)`");
+  let mut out = [0_u32; N];
+  out[0] = header(0x82, src);
+  let mut prev = 0_u16;
+  let mut i = 0;
+  while i < src.len() * 2 {
+    let h = (src[i / 2] >> (16 * (i % 2))) as u16;
+    let d = h.wrapping_sub(prev);
+    or_byte!(out, 4 + 2 * i, d & 0xFF);
+    or_byte!(out, 4 + 2 * i + 1, d >> 8);
+    prev = h;
+    i += 1;
+  }
+  Diff16Data(out)
+}
+
+// Known answers, worked out by hand from the formats in GBATEK, so that a
+// change to a compressor can't silently break it.
+const _: () = {
+  const fn words_eq(a: &[u32], b: &[u32]) -> bool {
+    if a.len() != b.len() {
+      return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+      if a[i] != b[i] {
+        return false;
+      }
+      i += 1;
+    }
+    true
+  }
+  const REPEAT: [u32; 4] = [0x0403_0201; 4];
+  const RUNS: [u32; 2] = [0x1111_1111, 0x2222_2222];
+  assert!(words_eq(
+    lz77_compress::<{ lz77_compressed_len(&REPEAT) }>(&REPEAT).as_words(),
+    &[0x0000_1010, 0x0302_0108, 0x0003_9004],
+  ));
+  assert!(words_eq(
+    rle_compress::<{ rle_compressed_len(&RUNS) }>(&RUNS).as_words(),
+    &[0x0000_0830, 0x2281_1181],
+  ));
+  assert!(words_eq(
+    huffman4_compress::<{ huffman4_compressed_len(&RUNS) }>(&RUNS).as_words(),
+    &[0x0000_0824, 0x0201_C001, 0x00FF_0000],
+  ));
+  assert!(words_eq(
+    diff8_filter::<{ diff_filtered_len(&REPEAT) }>(&REPEAT).as_words(),
+    &[0x0000_1081, 0x0101_0101, 0x0101_01FD, 0x0101_01FD, 0x0101_01FD],
+  ));
+  assert!(words_eq(
+    diff16_filter::<{ diff_filtered_len(&REPEAT) }>(&REPEAT).as_words(),
+    &[0x0000_1082, 0x0202_0201, 0x0202_FDFE, 0x0202_FDFE, 0x0202_FDFE],
+  ));
+};
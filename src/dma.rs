@@ -0,0 +1,363 @@
+//! Direct Memory Access (DMA) channels.
+//!
+//! There are four DMA channels. Each has a source address, a destination
+//! address, a transfer count, and a control register. Writing a control value
+//! with the enable bit set starts the transfer (or arms it, if the start
+//! timing isn't immediate). While a DMA runs the CPU is paused.
+//!
+//! * DMA0 is the highest priority, but can't read from the game pak.
+//! * DMA1 and DMA2 can be used to feed the sound FIFOs.
+//! * DMA3 is the only channel that can write to the game pak, and is the usual
+//!   channel for general copies.
+//!
+//! Because a DMA can write anywhere, writing the registers is `unsafe`. The
+//! [`dma3_copy_u32`] and [`dma3_copy`] helpers are safe wrappers for the
+//! common case of copying a slice into a block of memory.
+
+use core::ffi::c_void;
+
+use bitfrob::{u16_get_bit, u16_get_value, u16_with_bit, u16_with_value};
+use voladdress::{Safe, Unsafe, VolAddress, VolBlock};
+
+/// How the destination address changes after each unit is transferred.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum DestAddrControl {
+  #[default]
+  Increment = 0,
+  Decrement = 1,
+  Fixed = 2,
+  /// Increments during the transfer, then reloads the original address when
+  /// the transfer repeats.
+  IncrementReload = 3,
+}
+
+/// How the source address changes after each unit is transferred.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum SrcAddrControl {
+  #[default]
+  Increment = 0,
+  Decrement = 1,
+  Fixed = 2,
+}
+
+/// When a DMA transfer starts.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum DmaStartTiming {
+  #[default]
+  Immediate = 0,
+  VBlank = 1,
+  HBlank = 2,
+  /// Depends on the channel:
+  /// * DMA0: not used.
+  /// * DMA1 and DMA2: sound FIFO refills.
+  /// * DMA3: video capture.
+  Special = 3,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct DmaControl(u16);
+impl DmaControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+
+  #[inline]
+  pub const fn with_dest_addr(self, dest: DestAddrControl) -> Self {
+    Self(u16_with_value(5, 6, self.0, dest as u16))
+  }
+  #[inline]
+  pub const fn dest_addr(self) -> DestAddrControl {
+    match u16_get_value(5, 6, self.0) {
+      0 => DestAddrControl::Increment,
+      1 => DestAddrControl::Decrement,
+      2 => DestAddrControl::Fixed,
+      _ => DestAddrControl::IncrementReload,
+    }
+  }
+
+  #[inline]
+  pub const fn with_src_addr(self, src: SrcAddrControl) -> Self {
+    Self(u16_with_value(7, 8, self.0, src as u16))
+  }
+  #[inline]
+  pub const fn src_addr(self) -> SrcAddrControl {
+    match u16_get_value(7, 8, self.0) {
+      0 => SrcAddrControl::Increment,
+      1 => SrcAddrControl::Decrement,
+      _ => SrcAddrControl::Fixed,
+    }
+  }
+
+  /// If the transfer happens again at each start timing, until disabled.
+  ///
+  /// Has no effect with immediate timing.
+  #[inline]
+  pub const fn with_repeat(self, repeat: bool) -> Self {
+    Self(u16_with_bit(9, self.0, repeat))
+  }
+  #[inline]
+  pub const fn repeat(self) -> bool {
+    u16_get_bit(9, self.0)
+  }
+
+  /// Transfers 32-bit units instead of 16-bit units.
+  #[inline]
+  pub const fn with_32bit(self, bit32: bool) -> Self {
+    Self(u16_with_bit(10, self.0, bit32))
+  }
+  #[inline]
+  pub const fn is_32bit(self) -> bool {
+    u16_get_bit(10, self.0)
+  }
+
+  /// Game pak DRQ mode (DMA3 only).
+  #[inline]
+  pub const fn with_game_pak_drq(self, drq: bool) -> Self {
+    Self(u16_with_bit(11, self.0, drq))
+  }
+  #[inline]
+  pub const fn game_pak_drq(self) -> bool {
+    u16_get_bit(11, self.0)
+  }
+
+  #[inline]
+  pub const fn with_start_timing(self, timing: DmaStartTiming) -> Self {
+    Self(u16_with_value(12, 13, self.0, timing as u16))
+  }
+  #[inline]
+  pub const fn start_timing(self) -> DmaStartTiming {
+    match u16_get_value(12, 13, self.0) {
+      0 => DmaStartTiming::Immediate,
+      1 => DmaStartTiming::VBlank,
+      2 => DmaStartTiming::HBlank,
+      _ => DmaStartTiming::Special,
+    }
+  }
+
+  /// Sends an interrupt when the transfer completes.
+  #[inline]
+  pub const fn with_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(14, self.0, irq))
+  }
+  #[inline]
+  pub const fn irq(self) -> bool {
+    u16_get_bit(14, self.0)
+  }
+
+  /// Starts (or arms) the transfer. The hardware clears this when a
+  /// non-repeating transfer completes.
+  #[inline]
+  pub const fn with_enabled(self, enabled: bool) -> Self {
+    Self(u16_with_bit(15, self.0, enabled))
+  }
+  #[inline]
+  pub const fn enabled(self) -> bool {
+    u16_get_bit(15, self.0)
+  }
+}
+
+pub const DMA0_SRC: VolAddress<*const c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00B0) };
+pub const DMA0_DEST: VolAddress<*mut c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00B4) };
+/// The number of units to transfer. Only 14 bits are used, and 0 means
+/// `0x4000`.
+pub const DMA0_COUNT: VolAddress<u16, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00B8) };
+pub const DMA0_CONTROL: VolAddress<DmaControl, Safe, Unsafe> =
+  unsafe { VolAddress::new(0x0400_00BA) };
+
+pub const DMA1_SRC: VolAddress<*const c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00BC) };
+pub const DMA1_DEST: VolAddress<*mut c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00C0) };
+/// The number of units to transfer. Only 14 bits are used, and 0 means
+/// `0x4000`.
+pub const DMA1_COUNT: VolAddress<u16, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00C4) };
+pub const DMA1_CONTROL: VolAddress<DmaControl, Safe, Unsafe> =
+  unsafe { VolAddress::new(0x0400_00C6) };
+
+pub const DMA2_SRC: VolAddress<*const c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00C8) };
+pub const DMA2_DEST: VolAddress<*mut c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00CC) };
+/// The number of units to transfer. Only 14 bits are used, and 0 means
+/// `0x4000`.
+pub const DMA2_COUNT: VolAddress<u16, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00D0) };
+pub const DMA2_CONTROL: VolAddress<DmaControl, Safe, Unsafe> =
+  unsafe { VolAddress::new(0x0400_00D2) };
+
+pub const DMA3_SRC: VolAddress<*const c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00D4) };
+pub const DMA3_DEST: VolAddress<*mut c_void, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00D8) };
+/// The number of units to transfer. 0 means `0x1_0000`.
+pub const DMA3_COUNT: VolAddress<u16, (), Unsafe> =
+  unsafe { VolAddress::new(0x0400_00DC) };
+pub const DMA3_CONTROL: VolAddress<DmaControl, Safe, Unsafe> =
+  unsafe { VolAddress::new(0x0400_00DE) };
+
+/// Starts an immediate DMA3 transfer.
+///
+/// ## Safety
+/// * `count` units must be readable from `src` and writable to `dest`.
+/// * Both pointers must be aligned to the unit size.
+#[inline]
+unsafe fn dma3_immediate(
+  src: *const c_void, dest: *mut c_void, count: u16, bit32: bool,
+) {
+  DMA3_SRC.write(src);
+  DMA3_DEST.write(dest);
+  DMA3_COUNT.write(count);
+  DMA3_CONTROL.write(DmaControl::new().with_32bit(bit32).with_enabled(true));
+}
+
+/// Copies all of `src` to the start of `dest`, using DMA3 and 32-bit units.
+///
+/// ## Panics
+/// * If `src` is longer than `dest`.
+#[inline]
+pub fn dma3_copy_u32<const C: usize>(
+  src: &[u32], dest: VolBlock<u32, Safe, Safe, C>,
+) {
+  assert!(src.len() <= C);
+  assert!(src.len() <= 0x1_0000);
+  if src.is_empty() {
+    return;
+  }
+  unsafe {
+    dma3_immediate(
+      src.as_ptr().cast(),
+      dest.as_mut_ptr().cast(),
+      src.len() as u16,
+      true,
+    )
+  }
+}
+
+/// Copies all of `src` to the start of `dest`, using DMA3.
+///
+/// 32-bit units are used if `T` allows it, otherwise 16-bit units.
+///
+/// ## Panics
+/// * If `src` is longer than `dest`.
+/// * If `T` isn't a multiple of 2 bytes in size and alignment.
+#[inline]
+pub fn dma3_copy<T: Copy, const C: usize>(
+  src: &[T], dest: VolBlock<T, Safe, Safe, C>,
+) {
+  assert!(src.len() <= C);
+  if src.is_empty() {
+    return;
+  }
+  let bytes = core::mem::size_of_val(src);
+  let (count, bit32) =
+    if core::mem::size_of::<T>() % 4 == 0 && core::mem::align_of::<T>() >= 4 {
+      (bytes / 4, true)
+    } else if core::mem::size_of::<T>() % 2 == 0
+      && core::mem::align_of::<T>() >= 2
+    {
+      (bytes / 2, false)
+    } else {
+      panic!("DMA needs values of at least 2-byte size and alignment");
+    };
+  assert!(count <= 0x1_0000);
+  unsafe {
+    dma3_immediate(
+      src.as_ptr().cast(),
+      dest.as_mut_ptr().cast(),
+      count as u16,
+      bit32,
+    )
+  }
+}
+
+/// Streams a table of values into a display register, one per scanline, using
+/// HBlank DMA0.
+///
+/// Entry `n` of the table is the value used while drawing scanline `n`. Call
+/// [`vblank_restart`](Self::vblank_restart) once per frame during VBlank (eg:
+/// from the VBlank interrupt handler) to write entry 0 and re-arm the DMA.
+///
+/// Each HBlank copies the *next* entry, so the HBlank after line 159 copies
+/// entry 160. That's why the table has 161 entries: the last one lands in the
+/// register during VBlank and is overwritten by the next restart, so it's never
+/// seen, but it keeps the DMA from reading past the end of the table.
+///
+/// DMA0 can't read from the game pak, so the table must be in IWRAM or EWRAM
+/// (eg: a `static mut`), never in ROM. The table can be changed with
+/// [`table_mut`](Self::table_mut), and changes show up from the next line
+/// drawn, so rebuild it during VBlank to avoid tearing.
+///
+/// Only one effect can be active at a time, since they all use DMA0.
+pub struct RasterEffect<T: Copy + 'static> {
+  table: &'static mut [T; 161],
+  dest: usize,
+}
+impl<T: Copy + 'static> RasterEffect<T> {
+  /// Makes an effect that writes `table` into `dest`.
+  ///
+  /// ## Panics
+  /// * If `T` isn't 2 or 4 bytes.
+  /// * In debug builds, if `table` is in the game pak.
+  #[inline]
+  pub fn new<R>(
+    table: &'static mut [T; 161], dest: VolAddress<T, R, Safe>,
+  ) -> Self {
+    let size = core::mem::size_of::<T>();
+    assert!(size == 2 || size == 4);
+    debug_assert!(
+      (table.as_ptr() as usize) < 0x0800_0000,
+      "DMA0 can't read from the game pak"
+    );
+    Self { table, dest: dest.as_usize() }
+  }
+
+  /// The table of values, one per scanline (plus the padding entry).
+  #[inline]
+  pub fn table(&self) -> &[T; 161] {
+    self.table
+  }
+
+  /// Changes the table, such as to rebuild it for the next frame.
+  #[inline]
+  pub fn table_mut(&mut self) -> &mut [T; 161] {
+    self.table
+  }
+
+  /// Sets up the register and DMA0 for the start of a new frame.
+  #[inline]
+  pub fn vblank_restart(&self) {
+    let bit32 = core::mem::size_of::<T>() == 4;
+    let control = DmaControl::new()
+      .with_dest_addr(DestAddrControl::Fixed)
+      .with_src_addr(SrcAddrControl::Increment)
+      .with_repeat(true)
+      .with_32bit(bit32)
+      .with_start_timing(DmaStartTiming::HBlank)
+      .with_enabled(true);
+    unsafe {
+      DMA0_CONTROL.write(DmaControl::new());
+      (self.dest as *mut T).write_volatile(self.table[0]);
+      DMA0_SRC.write(self.table[1..].as_ptr().cast());
+      DMA0_DEST.write(self.dest as *mut c_void);
+      DMA0_COUNT.write(1);
+      DMA0_CONTROL.write(control);
+    }
+  }
+
+  /// Disables DMA0, stopping the effect.
+  ///
+  /// The register keeps whatever value it last had.
+  #[inline]
+  pub fn stop(&self) {
+    unsafe { DMA0_CONTROL.write(DmaControl::new()) };
+  }
+}
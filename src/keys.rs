@@ -0,0 +1,226 @@
+//! Tracking the keys across frames.
+//!
+//! [`KEYINPUT`] only gives the keys at the moment it's read. A [`Keys`] value
+//! is updated once per frame and remembers the previous frame too, so that it
+//! can tell a fresh press from a held key. The keys can come from any
+//! [`KeySource`].
+//!
+//! There's also support for waking from [`bios::stop`] with a key combo, and
+//! for soft resetting with a key combo, using the keypad interrupt.
+
+use crate::{
+  bios, set_irq_handler,
+  sound::{SoundStatus, SOUNDCNT_X},
+  DisplayControl, InterruptFlags, Irq, KeyControl, KeyInput, DISPCNT, IE, IME,
+  KEYCNT, KEYINPUT,
+};
+
+/// The usual soft reset combo: A + B + Start + Select.
+pub const SOFT_RESET_COMBO: KeyInput = KeyInput::from_pressed(0b1111);
+
+/// Something that gives the keys once per frame.
+///
+/// Game logic can read its keys through this trait so that the real keys can
+/// be swapped for a [`Replay`](crate::replay::Replay), or watched by a
+/// [`Recorder`](crate::replay::Recorder).
+pub trait KeySource {
+  fn read_keys(&mut self) -> KeyInput;
+}
+
+/// Reads the keys from [`KEYINPUT`].
+#[derive(Clone, Copy, Default)]
+pub struct HardwareKeys;
+impl KeySource for HardwareKeys {
+  #[inline]
+  fn read_keys(&mut self) -> KeyInput {
+    KEYINPUT.read()
+  }
+}
+
+/// The state of the keys over time.
+#[derive(Clone)]
+pub struct Keys {
+  current: KeyInput,
+  previous: KeyInput,
+  held: [u16; 10],
+  repeat_delay: u16,
+  repeat_rate: u16,
+}
+impl Keys {
+  /// No keys pressed, with auto-repeat off.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      current: KeyInput::NONE,
+      previous: KeyInput::NONE,
+      held: [0; 10],
+      repeat_delay: 0,
+      repeat_rate: 0,
+    }
+  }
+
+  /// Sets auto-repeat for [`repeated`](Self::repeated).
+  ///
+  /// After a key is held for `delay` frames it repeats once every `rate`
+  /// frames. A `rate` of 0 turns auto-repeat off.
+  #[inline]
+  pub const fn with_repeat(self, delay: u16, rate: u16) -> Self {
+    Self { repeat_delay: delay, repeat_rate: rate, ..self }
+  }
+
+  /// Reads [`KEYINPUT`] as the keys for a new frame.
+  #[inline]
+  pub fn update(&mut self) {
+    self.update_with(KEYINPUT.read());
+  }
+
+  /// Reads `source` as the keys for a new frame.
+  #[inline]
+  pub fn update_from(&mut self, source: &mut impl KeySource) {
+    self.update_with(source.read_keys());
+  }
+
+  /// Uses `keys` as the keys for a new frame.
+  pub fn update_with(&mut self, keys: KeyInput) {
+    self.previous = self.current;
+    self.current = keys;
+    let pressed = keys.pressed();
+    for (i, held) in self.held.iter_mut().enumerate() {
+      *held = if pressed & (1 << i) != 0 { held.saturating_add(1) } else { 0 };
+    }
+  }
+
+  /// The keys pressed this frame.
+  #[inline]
+  pub const fn current(&self) -> KeyInput {
+    self.current
+  }
+  /// The keys pressed last frame.
+  #[inline]
+  pub const fn previous(&self) -> KeyInput {
+    self.previous
+  }
+
+  /// Keys pressed this frame that weren't pressed last frame.
+  #[inline]
+  pub const fn just_pressed(&self) -> KeyInput {
+    KeyInput::from_pressed(self.current.pressed() & !self.previous.pressed())
+  }
+  /// Keys pressed last frame that aren't pressed this frame.
+  #[inline]
+  pub const fn just_released(&self) -> KeyInput {
+    KeyInput::from_pressed(self.previous.pressed() & !self.current.pressed())
+  }
+
+  /// How many frames in a row all of `keys` have been pressed (0 if any of
+  /// them isn't pressed).
+  #[inline]
+  pub fn held_frames(&self, keys: KeyInput) -> u16 {
+    keys
+      .iter()
+      .map(|k| self.held[k.pressed().trailing_zeros() as usize])
+      .min()
+      .unwrap_or(0)
+  }
+
+  /// Keys that were just pressed, plus held keys that auto-repeat this frame.
+  ///
+  /// Useful for menus, where holding a direction should keep moving the
+  /// cursor.
+  pub fn repeated(&self) -> KeyInput {
+    let mut bits = 0;
+    for (i, &held) in self.held.iter().enumerate() {
+      let repeat = self.repeat_rate != 0
+        && held > self.repeat_delay
+        && (held - self.repeat_delay) % self.repeat_rate == 0;
+      if held == 1 || repeat {
+        bits |= 1 << i;
+      }
+    }
+    KeyInput::from_pressed(bits)
+  }
+}
+impl Default for Keys {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Puts the GBA into low power mode until all of `keys` are pressed at once.
+///
+/// First this waits for `keys` to be released (otherwise the combo used to go
+/// to sleep would instantly wake it), and after waking it waits for them to be
+/// released again. The display is blanked and the sound circuit is turned off
+/// while asleep. Turning sound off clears the PSG registers, so any PSG sounds
+/// need to be set up again.
+///
+/// The keypad interrupt handler is removed while asleep, so this works along
+/// with [`enable_soft_reset_combo`], even using the same combo. The handler,
+/// `KEYCNT`, `IE`, `IME`, `DISPCNT`, and `SOUNDCNT_X` are restored afterwards.
+///
+/// ## Panics
+/// * If `keys` is empty, since nothing could ever wake the GBA.
+pub fn sleep_until_keys(keys: KeyInput) {
+  assert!(keys.any(), "no keys to wake with");
+  while KEYINPUT.read().contains(keys) {}
+
+  let old_keycnt = KEYCNT.read();
+  let old_ie = IE.read();
+  let old_ime = IME.read();
+  let old_dispcnt = DISPCNT.read();
+  let old_soundcnt_x = SOUNDCNT_X.read();
+
+  IME.write(false);
+  let old_handler = set_irq_handler(Irq::Keypad, None);
+  KEYCNT.write(
+    KeyControl::new().with_keys(keys).with_irq(true).with_all_keys(true),
+  );
+  IE.write(InterruptFlags::new().with_keypad(true));
+  DISPCNT.write(DisplayControl::new().with_forced_blank(true));
+  SOUNDCNT_X.write(SoundStatus::new());
+  IME.write(true);
+
+  bios::stop();
+
+  IME.write(false);
+  // Wait for the keys to be released again, so that waking with the soft reset
+  // combo doesn't set off the reset as soon as its handler is back.
+  while KEYINPUT.read().contains(keys) {}
+  SOUNDCNT_X.write(old_soundcnt_x);
+  DISPCNT.write(old_dispcnt);
+  KEYCNT.write(old_keycnt);
+  set_irq_handler(Irq::Keypad, old_handler);
+  IE.write(old_ie);
+  IME.write(old_ime);
+}
+
+fn soft_reset_handler() {
+  // Resetting with the combo still held would fire the interrupt again as soon
+  // as the game re-enables it, so wait for the keys to be let go.
+  let keys = KEYCNT.read().keys();
+  while KEYINPUT.read().contains(keys) {}
+  // SoftReset clears the IRQ vector but not the IO registers, so an interrupt
+  // before `_start` sets the vector again would jump to address 0.
+  IME.write(false);
+  IE.write(InterruptFlags::new());
+  bios::soft_reset();
+}
+
+/// Soft resets the game whenever all of `keys` are pressed at once.
+///
+/// The reset happens once the keys are released again, so the game doesn't
+/// reset a second time when it turns the combo back on. This uses `KEYCNT`
+/// and the keypad interrupt handler, and enables the keypad interrupt in `IE`.
+/// Interrupts must also be enabled with `IME`.
+///
+/// ## Panics
+/// * If `keys` is empty.
+pub fn enable_soft_reset_combo(keys: KeyInput) {
+  assert!(keys.any(), "no keys for the reset combo");
+  KEYCNT.write(
+    KeyControl::new().with_keys(keys).with_irq(true).with_all_keys(true),
+  );
+  set_irq_handler(Irq::Keypad, Some(soft_reset_handler));
+  IE.write(IE.read().with_keypad(true));
+}
@@ -1,9 +1,21 @@
 #![no_std]
 #![feature(naked_functions)]
 
-use bitfrob::{u16_get_bit, u16_with_bit, u16_with_value};
+use bitfrob::{u16_get_bit, u16_get_value, u16_with_bit, u16_with_value};
 use voladdress::{Safe, VolAddress, VolBlock, VolSeries};
 
+pub mod bios;
+pub mod compress;
+pub mod dma;
+pub mod keys;
+pub mod log;
+#[cfg(feature = "panic_handler")]
+mod panic;
+pub mod profile;
+pub mod replay;
+pub mod sound;
+pub mod timer;
+
 macro_rules! kilobytes {
   ($bytes:expr) => {
     $bytes * 1024
@@ -13,15 +25,130 @@ macro_rules! kilobytes {
 pub const DISPCNT: VolAddress<DisplayControl, Safe, Safe> =
   unsafe { VolAddress::new(0x0400_0000) };
 
+pub const DISPSTAT: VolAddress<DisplayStatus, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0004) };
+
+/// The scanline currently being drawn, `0..=227`.
+///
+/// Lines 160 and above are the vertical blank.
+pub const VCOUNT: VolAddress<u16, Safe, ()> =
+  unsafe { VolAddress::new(0x0400_0006) };
+
+pub const BG0CNT: VolAddress<BackgroundControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0008) };
+pub const BG1CNT: VolAddress<BackgroundControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_000A) };
+pub const BG2CNT: VolAddress<BackgroundControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_000C) };
+pub const BG3CNT: VolAddress<BackgroundControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_000E) };
+
+pub const BG0HOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0010) };
+pub const BG0VOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0012) };
+pub const BG1HOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0014) };
+pub const BG1VOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0016) };
+pub const BG2HOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0018) };
+pub const BG2VOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_001A) };
+pub const BG3HOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_001C) };
+pub const BG3VOFS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_001E) };
+
+pub const BG2PA: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0020) };
+pub const BG2PB: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0022) };
+pub const BG2PC: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0024) };
+pub const BG2PD: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0026) };
+pub const BG2X: VolAddress<I32F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0028) };
+pub const BG2Y: VolAddress<I32F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_002C) };
+
+pub const BG3PA: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0030) };
+pub const BG3PB: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0032) };
+pub const BG3PC: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0034) };
+pub const BG3PD: VolAddress<I16F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0036) };
+pub const BG3X: VolAddress<I32F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0038) };
+pub const BG3Y: VolAddress<I32F8, (), Safe> =
+  unsafe { VolAddress::new(0x0400_003C) };
+
 pub const KEYINPUT: VolAddress<KeyInput, Safe, ()> =
   unsafe { VolAddress::new(0x400_0130) };
 
+pub const KEYCNT: VolAddress<KeyControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0132) };
+
 pub const BACKDROP: VolAddress<Color, Safe, Safe> =
   unsafe { VolAddress::new(0x0500_0000) };
 
+pub const BG_PALETTE: VolBlock<Color, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0500_0000) };
+
 pub const OBJ_PALETTE: VolBlock<Color, Safe, Safe, 256> =
   unsafe { VolBlock::new(0x0500_0200) };
 
+/// One of the 16 color palette banks used by 4bpp backgrounds.
+///
+/// ## Panics
+/// * If `n` isn't a valid palette bank, `0..=15`.
+#[inline]
+pub const fn bg_palbank(n: usize) -> VolBlock<Color, Safe, Safe, 16> {
+  assert!(n < 16);
+  unsafe { VolBlock::new(0x0500_0000 + n * 32) }
+}
+
+/// One of the 16 color palette banks used by 4bpp objects.
+///
+/// ## Panics
+/// * If `n` isn't a valid palette bank, `0..=15`.
+#[inline]
+pub const fn obj_palbank(n: usize) -> VolBlock<Color, Safe, Safe, 16> {
+  assert!(n < 16);
+  unsafe { VolBlock::new(0x0500_0200 + n * 32) }
+}
+
+/// Copies `colors` to the start of `dest`, two colors per 32-bit write.
+///
+/// ## Panics
+/// * If `colors` is longer than `dest`.
+pub fn load_palette<const C: usize>(
+  dest: VolBlock<Color, Safe, Safe, C>, colors: &[Color],
+) {
+  assert!(colors.len() <= C);
+  let mut colors = colors;
+  let mut addr = dest.as_usize();
+  if addr % 4 != 0 {
+    if let Some((first, rest)) = colors.split_first() {
+      dest.index(0).write(*first);
+      colors = rest;
+      addr += 2;
+    }
+  }
+  let mut pairs = colors.chunks_exact(2);
+  for pair in &mut pairs {
+    let word = pair[0].0 as u32 | ((pair[1].0 as u32) << 16);
+    unsafe { VolAddress::<u32, Safe, Safe>::new(addr) }.write(word);
+    addr += 4;
+  }
+  if let [last] = pairs.remainder() {
+    unsafe { VolAddress::<Color, Safe, Safe>::new(addr) }.write(*last);
+  }
+}
+
 pub const PIXELS_PER_TILE: usize = 8 * 8;
 pub const BITS_PER_BYTE: usize = 8;
 pub const SIZE_OF_TILE4: usize = (PIXELS_PER_TILE * 4) / BITS_PER_BYTE;
@@ -43,6 +170,231 @@ pub type Tile8 = [u32; TILE8_WORD_COUNT];
 pub const OBJ_TILE8: VolSeries<Tile8, Safe, Safe, 1023, 32> =
   unsafe { VolSeries::new(0x0601_0000) };
 
+pub const CHARBLOCK0_TILE4: VolBlock<Tile4, Safe, Safe, 512> =
+  unsafe { VolBlock::new(0x0600_0000) };
+pub const CHARBLOCK0_TILE8: VolBlock<Tile8, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0600_0000) };
+pub const CHARBLOCK1_TILE4: VolBlock<Tile4, Safe, Safe, 512> =
+  unsafe { VolBlock::new(0x0600_4000) };
+pub const CHARBLOCK1_TILE8: VolBlock<Tile8, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0600_4000) };
+pub const CHARBLOCK2_TILE4: VolBlock<Tile4, Safe, Safe, 512> =
+  unsafe { VolBlock::new(0x0600_8000) };
+pub const CHARBLOCK2_TILE8: VolBlock<Tile8, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0600_8000) };
+pub const CHARBLOCK3_TILE4: VolBlock<Tile4, Safe, Safe, 512> =
+  unsafe { VolBlock::new(0x0600_C000) };
+pub const CHARBLOCK3_TILE8: VolBlock<Tile8, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0600_C000) };
+
+/// A text background tilemap entry.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct TextEntry(pub u16);
+impl TextEntry {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  #[inline]
+  pub const fn with_tile(self, tile: u16) -> Self {
+    Self(u16_with_value(0, 9, self.0, tile))
+  }
+  #[inline]
+  pub const fn tile(self) -> u16 {
+    u16_get_value(0, 9, self.0)
+  }
+  #[inline]
+  pub const fn with_hflip(self, hflip: bool) -> Self {
+    Self(u16_with_bit(10, self.0, hflip))
+  }
+  #[inline]
+  pub const fn hflip(self) -> bool {
+    u16_get_bit(10, self.0)
+  }
+  #[inline]
+  pub const fn with_vflip(self, vflip: bool) -> Self {
+    Self(u16_with_bit(11, self.0, vflip))
+  }
+  #[inline]
+  pub const fn vflip(self) -> bool {
+    u16_get_bit(11, self.0)
+  }
+  /// The palette bank used by 4bpp tiles (ignored with 8bpp tiles).
+  #[inline]
+  pub const fn with_palbank(self, palbank: u16) -> Self {
+    Self(u16_with_value(12, 15, self.0, palbank))
+  }
+  #[inline]
+  pub const fn palbank(self) -> u16 {
+    u16_get_value(12, 15, self.0)
+  }
+}
+
+/// An affine background tilemap entry, which is just a tile index.
+///
+/// VRAM can't be written one byte at a time, so affine entries have to be
+/// written in pairs. See [`affine_screenblock`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct AffineEntry(pub u8);
+impl AffineEntry {
+  #[inline]
+  pub const fn new(tile: u8) -> Self {
+    Self(tile)
+  }
+  #[inline]
+  pub const fn tile(self) -> u8 {
+    self.0
+  }
+  /// Packs two entries, with `self` at the lower address.
+  #[inline]
+  pub const fn pair(self, next: Self) -> u16 {
+    self.0 as u16 | ((next.0 as u16) << 8)
+  }
+}
+
+/// Each screenblock is a 32x32 text tilemap.
+///
+/// Larger text backgrounds use several screenblocks in a row.
+pub const SCREENBLOCK0: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_0000) };
+pub const SCREENBLOCK1: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_0800) };
+pub const SCREENBLOCK2: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_1000) };
+pub const SCREENBLOCK3: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_1800) };
+pub const SCREENBLOCK4: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_2000) };
+pub const SCREENBLOCK5: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_2800) };
+pub const SCREENBLOCK6: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_3000) };
+pub const SCREENBLOCK7: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_3800) };
+pub const SCREENBLOCK8: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_4000) };
+pub const SCREENBLOCK9: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_4800) };
+pub const SCREENBLOCK10: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_5000) };
+pub const SCREENBLOCK11: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_5800) };
+pub const SCREENBLOCK12: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_6000) };
+pub const SCREENBLOCK13: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_6800) };
+pub const SCREENBLOCK14: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_7000) };
+pub const SCREENBLOCK15: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_7800) };
+pub const SCREENBLOCK16: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_8000) };
+pub const SCREENBLOCK17: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_8800) };
+pub const SCREENBLOCK18: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_9000) };
+pub const SCREENBLOCK19: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_9800) };
+pub const SCREENBLOCK20: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_A000) };
+pub const SCREENBLOCK21: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_A800) };
+pub const SCREENBLOCK22: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_B000) };
+pub const SCREENBLOCK23: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_B800) };
+pub const SCREENBLOCK24: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_C000) };
+pub const SCREENBLOCK25: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_C800) };
+pub const SCREENBLOCK26: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_D000) };
+pub const SCREENBLOCK27: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_D800) };
+pub const SCREENBLOCK28: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_E000) };
+pub const SCREENBLOCK29: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_E800) };
+pub const SCREENBLOCK30: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_F000) };
+pub const SCREENBLOCK31: VolBlock<TextEntry, Safe, Safe, 1024> =
+  unsafe { VolBlock::new(0x0600_F800) };
+
+/// A screenblock as affine tilemap entry pairs (see [`AffineEntry::pair`]).
+///
+/// An affine tilemap is `size * size` bytes in a row, so maps larger than
+/// 32x32 continue into the following screenblocks.
+///
+/// ## Panics
+/// * If `n` isn't a valid screenblock, `0..=31`.
+#[inline]
+pub const fn affine_screenblock(n: usize) -> VolBlock<u16, Safe, Safe, 1024> {
+  assert!(n < 32);
+  unsafe { VolBlock::new(0x0600_0000 + n * 0x800) }
+}
+
+/// Battery backed SRAM on the game pak (if the cart has it).
+///
+/// SRAM is on an 8-bit bus, so it must be accessed one byte at a time.
+pub const SRAM: VolBlock<u8, Safe, Safe, { kilobytes!(32) }> =
+  unsafe { VolBlock::new(0x0E00_0000) };
+
+/// How an object is displayed, if at all.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum ObjDisplayStyle {
+  #[default]
+  Normal = 0,
+  /// Uses the affine matrix selected by the object's affine index.
+  Affine = 1,
+  NotDisplayed = 2,
+  /// Affine, with the drawing area doubled so that rotated pixels outside of
+  /// the object's normal bounds aren't clipped.
+  DoubleSizeAffine = 3,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum ObjMode {
+  #[default]
+  Normal = 0,
+  /// Always blends with the layers below, even if not selected in the
+  /// blending controls.
+  SemiTransparent = 1,
+  /// Not drawn, and instead opaque pixels define the object window.
+  Window = 2,
+}
+
+/// The shape of an object, which combines with an [`ObjSize`].
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum ObjShape {
+  #[default]
+  Square = 0,
+  Wide = 1,
+  Tall = 2,
+}
+
+/// The size of an object, in pixels, depending on its [`ObjShape`].
+///
+/// | Size  | Square | Wide  | Tall  |
+/// |:-----:|:------:|:-----:|:-----:|
+/// | Size0 | 8x8    | 16x8  | 8x16  |
+/// | Size1 | 16x16  | 32x8  | 8x32  |
+/// | Size2 | 32x32  | 32x16 | 16x32 |
+/// | Size3 | 64x64  | 64x32 | 32x64 |
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum ObjSize {
+  #[default]
+  Size0 = 0,
+  Size1 = 1,
+  Size2 = 2,
+  Size3 = 3,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
 #[repr(transparent)]
 pub struct ObjAttr0(pub u16);
@@ -56,6 +408,70 @@ impl ObjAttr0 {
   pub const fn with_y(self, y: i16) -> Self {
     Self(u16_with_value(0, 7, self.0, y as u16))
   }
+  #[inline]
+  pub const fn y(self) -> u16 {
+    u16_get_value(0, 7, self.0)
+  }
+
+  #[inline]
+  pub const fn with_style(self, style: ObjDisplayStyle) -> Self {
+    Self(u16_with_value(8, 9, self.0, style as u16))
+  }
+  #[inline]
+  pub const fn style(self) -> ObjDisplayStyle {
+    match u16_get_value(8, 9, self.0) {
+      0 => ObjDisplayStyle::Normal,
+      1 => ObjDisplayStyle::Affine,
+      2 => ObjDisplayStyle::NotDisplayed,
+      _ => ObjDisplayStyle::DoubleSizeAffine,
+    }
+  }
+
+  #[inline]
+  pub const fn with_mode(self, mode: ObjMode) -> Self {
+    Self(u16_with_value(10, 11, self.0, mode as u16))
+  }
+  #[inline]
+  pub const fn mode(self) -> ObjMode {
+    match u16_get_value(10, 11, self.0) {
+      0 => ObjMode::Normal,
+      1 => ObjMode::SemiTransparent,
+      // 3 is invalid, and can't be set with `with_mode`
+      _ => ObjMode::Window,
+    }
+  }
+
+  #[inline]
+  pub const fn with_mosaic(self, mosaic: bool) -> Self {
+    Self(u16_with_bit(12, self.0, mosaic))
+  }
+  #[inline]
+  pub const fn mosaic(self) -> bool {
+    u16_get_bit(12, self.0)
+  }
+
+  #[inline]
+  pub const fn with_8bpp(self, bpp8: bool) -> Self {
+    Self(u16_with_bit(13, self.0, bpp8))
+  }
+  #[inline]
+  pub const fn is_8bpp(self) -> bool {
+    u16_get_bit(13, self.0)
+  }
+
+  #[inline]
+  pub const fn with_shape(self, shape: ObjShape) -> Self {
+    Self(u16_with_value(14, 15, self.0, shape as u16))
+  }
+  #[inline]
+  pub const fn shape(self) -> ObjShape {
+    match u16_get_value(14, 15, self.0) {
+      0 => ObjShape::Square,
+      1 => ObjShape::Wide,
+      // 3 is invalid, and can't be set with `with_shape`
+      _ => ObjShape::Tall,
+    }
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
@@ -68,13 +484,56 @@ impl ObjAttr1 {
   }
 
   #[inline]
-  pub const fn with_size(self, size: u16) -> Self {
-    Self(u16_with_value(14, 15, self.0, size))
+  pub const fn with_x(self, x: i16) -> Self {
+    Self(u16_with_value(0, 8, self.0, x as u16))
+  }
+  #[inline]
+  pub const fn x(self) -> u16 {
+    u16_get_value(0, 8, self.0)
   }
 
+  /// The affine matrix, `0..=31`, used by affine objects.
+  ///
+  /// This overlaps the flip bits, which only apply to non-affine objects.
   #[inline]
-  pub const fn with_x(self, x: i16) -> Self {
-    Self(u16_with_value(0, 9, self.0, x as u16))
+  pub const fn with_affine_index(self, index: u16) -> Self {
+    Self(u16_with_value(9, 13, self.0, index))
+  }
+  #[inline]
+  pub const fn affine_index(self) -> u16 {
+    u16_get_value(9, 13, self.0)
+  }
+
+  #[inline]
+  pub const fn with_hflip(self, hflip: bool) -> Self {
+    Self(u16_with_bit(12, self.0, hflip))
+  }
+  #[inline]
+  pub const fn hflip(self) -> bool {
+    u16_get_bit(12, self.0)
+  }
+
+  #[inline]
+  pub const fn with_vflip(self, vflip: bool) -> Self {
+    Self(u16_with_bit(13, self.0, vflip))
+  }
+  #[inline]
+  pub const fn vflip(self) -> bool {
+    u16_get_bit(13, self.0)
+  }
+
+  #[inline]
+  pub const fn with_size(self, size: ObjSize) -> Self {
+    Self(u16_with_value(14, 15, self.0, size as u16))
+  }
+  #[inline]
+  pub const fn size(self) -> ObjSize {
+    match u16_get_value(14, 15, self.0) {
+      0 => ObjSize::Size0,
+      1 => ObjSize::Size1,
+      2 => ObjSize::Size2,
+      _ => ObjSize::Size3,
+    }
   }
 }
 
@@ -91,13 +550,37 @@ impl ObjAttr2 {
   pub const fn with_tile(self, tile: u16) -> Self {
     Self(u16_with_value(0, 9, self.0, tile))
   }
+  #[inline]
+  pub const fn tile(self) -> u16 {
+    u16_get_value(0, 9, self.0)
+  }
+
+  /// Draw priority, `0..=3`, relative to the backgrounds.
+  #[inline]
+  pub const fn with_priority(self, priority: u16) -> Self {
+    Self(u16_with_value(10, 11, self.0, priority))
+  }
+  #[inline]
+  pub const fn priority(self) -> u16 {
+    u16_get_value(10, 11, self.0)
+  }
+
+  /// The palette bank used by 4bpp objects (ignored with 8bpp).
+  #[inline]
+  pub const fn with_palbank(self, palbank: u16) -> Self {
+    Self(u16_with_value(12, 15, self.0, palbank))
+  }
+  #[inline]
+  pub const fn palbank(self) -> u16 {
+    u16_get_value(12, 15, self.0)
+  }
 }
 
-pub const OBJ_ATTRS_0: VolSeries<ObjAttr0, Safe, Safe, 128, 64> =
+pub const OBJ_ATTRS_0: VolSeries<ObjAttr0, Safe, Safe, 128, 8> =
   unsafe { VolSeries::new(0x0700_0000) };
-pub const OBJ_ATTRS_1: VolSeries<ObjAttr1, Safe, Safe, 128, 64> =
+pub const OBJ_ATTRS_1: VolSeries<ObjAttr1, Safe, Safe, 128, 8> =
   unsafe { VolSeries::new(0x0700_0000 + 2) };
-pub const OBJ_ATTRS_2: VolSeries<ObjAttr2, Safe, Safe, 128, 64> =
+pub const OBJ_ATTRS_2: VolSeries<ObjAttr2, Safe, Safe, 128, 8> =
   unsafe { VolSeries::new(0x0700_0000 + 4) };
 
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
@@ -109,26 +592,319 @@ impl ObjAttr {
     Self(ObjAttr0::new(), ObjAttr1::new(), ObjAttr2::new())
   }
   #[inline]
-  pub const fn with_size(self, size: u16) -> Self {
+  pub const fn with_y(self, y: i16) -> Self {
+    Self(self.0.with_y(y), self.1, self.2)
+  }
+  #[inline]
+  pub const fn y(self) -> u16 {
+    self.0.y()
+  }
+  #[inline]
+  pub const fn with_style(self, style: ObjDisplayStyle) -> Self {
+    Self(self.0.with_style(style), self.1, self.2)
+  }
+  #[inline]
+  pub const fn style(self) -> ObjDisplayStyle {
+    self.0.style()
+  }
+  #[inline]
+  pub const fn with_mode(self, mode: ObjMode) -> Self {
+    Self(self.0.with_mode(mode), self.1, self.2)
+  }
+  #[inline]
+  pub const fn mode(self) -> ObjMode {
+    self.0.mode()
+  }
+  #[inline]
+  pub const fn with_mosaic(self, mosaic: bool) -> Self {
+    Self(self.0.with_mosaic(mosaic), self.1, self.2)
+  }
+  #[inline]
+  pub const fn mosaic(self) -> bool {
+    self.0.mosaic()
+  }
+  #[inline]
+  pub const fn with_8bpp(self, bpp8: bool) -> Self {
+    Self(self.0.with_8bpp(bpp8), self.1, self.2)
+  }
+  #[inline]
+  pub const fn is_8bpp(self) -> bool {
+    self.0.is_8bpp()
+  }
+  #[inline]
+  pub const fn with_shape(self, shape: ObjShape) -> Self {
+    Self(self.0.with_shape(shape), self.1, self.2)
+  }
+  #[inline]
+  pub const fn shape(self) -> ObjShape {
+    self.0.shape()
+  }
+  #[inline]
+  pub const fn with_x(self, x: i16) -> Self {
+    Self(self.0, self.1.with_x(x), self.2)
+  }
+  #[inline]
+  pub const fn x(self) -> u16 {
+    self.1.x()
+  }
+  #[inline]
+  pub const fn with_affine_index(self, index: u16) -> Self {
+    Self(self.0, self.1.with_affine_index(index), self.2)
+  }
+  #[inline]
+  pub const fn affine_index(self) -> u16 {
+    self.1.affine_index()
+  }
+  #[inline]
+  pub const fn with_hflip(self, hflip: bool) -> Self {
+    Self(self.0, self.1.with_hflip(hflip), self.2)
+  }
+  #[inline]
+  pub const fn hflip(self) -> bool {
+    self.1.hflip()
+  }
+  #[inline]
+  pub const fn with_vflip(self, vflip: bool) -> Self {
+    Self(self.0, self.1.with_vflip(vflip), self.2)
+  }
+  #[inline]
+  pub const fn vflip(self) -> bool {
+    self.1.vflip()
+  }
+  #[inline]
+  pub const fn with_size(self, size: ObjSize) -> Self {
     Self(self.0, self.1.with_size(size), self.2)
   }
   #[inline]
+  pub const fn size(self) -> ObjSize {
+    self.1.size()
+  }
+  #[inline]
   pub const fn with_tile(self, tile: u16) -> Self {
     Self(self.0, self.1, self.2.with_tile(tile))
   }
   #[inline]
-  pub const fn with_x(self, x: i16) -> Self {
-    Self(self.0, self.1.with_x(x), self.2)
+  pub const fn tile(self) -> u16 {
+    self.2.tile()
   }
   #[inline]
-  pub const fn with_y(self, y: i16) -> Self {
-    Self(self.0.with_y(y), self.1, self.2)
+  pub const fn with_priority(self, priority: u16) -> Self {
+    Self(self.0, self.1, self.2.with_priority(priority))
+  }
+  #[inline]
+  pub const fn priority(self) -> u16 {
+    self.2.priority()
+  }
+  #[inline]
+  pub const fn with_palbank(self, palbank: u16) -> Self {
+    Self(self.0, self.1, self.2.with_palbank(palbank))
+  }
+  #[inline]
+  pub const fn palbank(self) -> u16 {
+    self.2.palbank()
   }
 }
 
-pub const OBJ_ATTRS: VolSeries<ObjAttr, Safe, Safe, 128, 64> =
+pub const OBJ_ATTRS: VolSeries<ObjAttr, Safe, Safe, 128, 8> =
   unsafe { VolSeries::new(0x0700_0000) };
 
+// The 32 object affine matrices are stored in the 4th halfword of each group
+// of four `ObjAttr` slots.
+pub const OBJ_AFFINE_PA: VolSeries<I16F8, Safe, Safe, 32, 32> =
+  unsafe { VolSeries::new(0x0700_0006) };
+pub const OBJ_AFFINE_PB: VolSeries<I16F8, Safe, Safe, 32, 32> =
+  unsafe { VolSeries::new(0x0700_000E) };
+pub const OBJ_AFFINE_PC: VolSeries<I16F8, Safe, Safe, 32, 32> =
+  unsafe { VolSeries::new(0x0700_0016) };
+pub const OBJ_AFFINE_PD: VolSeries<I16F8, Safe, Safe, 32, 32> =
+  unsafe { VolSeries::new(0x0700_001E) };
+
+/// `sin` of the first quarter turn, in 256ths of a turn, as 2.14 fixed point.
+#[rustfmt::skip]
+const QUARTER_SINE: [i16; 65] = [
+  0, 402, 804, 1205, 1606, 2006, 2404, 2801,
+  3196, 3590, 3981, 4370, 4756, 5139, 5520, 5897,
+  6270, 6639, 7005, 7366, 7723, 8076, 8423, 8765,
+  9102, 9434, 9760, 10080, 10394, 10702, 11003, 11297,
+  11585, 11866, 12140, 12406, 12665, 12916, 13160, 13395,
+  13623, 13842, 14053, 14256, 14449, 14635, 14811, 14978,
+  15137, 15286, 15426, 15557, 15679, 15791, 15893, 15986,
+  16069, 16143, 16207, 16261, 16305, 16340, 16364, 16379,
+  16384,
+];
+
+/// `sin(angle)` as 2.14 fixed point, where `0x1_0000` is a full turn.
+///
+/// Only the upper 8 bits of the angle are used, the same as the BIOS.
+const fn sin_2_14(angle: u16) -> i32 {
+  let a = (angle >> 8) as usize;
+  let i = a & 63;
+  match a >> 6 {
+    0 => QUARTER_SINE[i] as i32,
+    1 => QUARTER_SINE[64 - i] as i32,
+    2 => -(QUARTER_SINE[i] as i32),
+    _ => -(QUARTER_SINE[64 - i] as i32),
+  }
+}
+
+/// An affine transformation matrix.
+///
+/// The matrix maps from *screen* space into *texture* space, so it's the
+/// inverse of how the image appears to change. Eg: scaling by 2.0 makes an
+/// object appear half as big.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct AffineMatrix {
+  pub pa: I16F8,
+  pub pb: I16F8,
+  pub pc: I16F8,
+  pub pd: I16F8,
+}
+impl AffineMatrix {
+  pub const IDENTITY: Self =
+    Self { pa: I16F8::ONE, pb: I16F8(0), pc: I16F8(0), pd: I16F8::ONE };
+
+  /// Scales by `sx` horizontally and `sy` vertically (in texture pixels per
+  /// screen pixel).
+  #[inline]
+  #[must_use]
+  pub const fn scale(sx: I16F8, sy: I16F8) -> Self {
+    Self { pa: sx, pb: I16F8(0), pc: I16F8(0), pd: sy }
+  }
+
+  /// Rotates by `angle`, where `0x1_0000` is a full turn counter-clockwise.
+  #[inline]
+  #[must_use]
+  pub const fn rotation(angle: u16) -> Self {
+    Self::rotation_scale(I16F8::ONE, I16F8::ONE, angle)
+  }
+
+  /// Scales and rotates, the same as the BIOS `ObjAffineSet` function.
+  ///
+  /// Only the upper 8 bits of `angle` are used.
+  #[inline]
+  #[must_use]
+  pub const fn rotation_scale(sx: I16F8, sy: I16F8, angle: u16) -> Self {
+    let sin = sin_2_14(angle);
+    let cos = sin_2_14(angle.wrapping_add(0x4000));
+    let sx = sx.0 as i32;
+    let sy = sy.0 as i32;
+    Self {
+      pa: I16F8(((sx * cos) >> 14) as i16),
+      pb: I16F8(((-sx * sin) >> 14) as i16),
+      pc: I16F8(((sy * sin) >> 14) as i16),
+      pd: I16F8(((sy * cos) >> 14) as i16),
+    }
+  }
+}
+impl Default for AffineMatrix {
+  #[inline]
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+/// Writes an affine matrix into OAM, for objects to use with their
+/// `affine_index`.
+#[inline]
+pub fn write_obj_affine(index: usize, matrix: AffineMatrix) {
+  OBJ_AFFINE_PA.index(index).write(matrix.pa);
+  OBJ_AFFINE_PB.index(index).write(matrix.pb);
+  OBJ_AFFINE_PC.index(index).write(matrix.pc);
+  OBJ_AFFINE_PD.index(index).write(matrix.pd);
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct OamSlot {
+  attr: ObjAttr,
+  affine: I16F8,
+}
+
+/// An in-memory copy of all of OAM.
+///
+/// Edit this freely during the frame, then [`commit`](Self::commit) it during
+/// VBlank so that objects never tear. Put it in IWRAM so that the copy is as
+/// fast as possible:
+///
+/// ```no_run
+/// # use gba_from_scratch::*;
+/// #[link_section = ".iwram"]
+/// static mut OAM_SHADOW: OamShadow = OamShadow::new();
+/// ```
+#[derive(Clone)]
+#[repr(C, align(4))]
+pub struct OamShadow([OamSlot; 128]);
+impl OamShadow {
+  /// All objects hidden, all affine matrices the identity matrix.
+  #[inline]
+  pub const fn new() -> Self {
+    let hidden = ObjAttr::new().with_style(ObjDisplayStyle::NotDisplayed);
+    let mut slots = [OamSlot { attr: hidden, affine: I16F8(0) }; 128];
+    let mut i = 0;
+    while i < 32 {
+      slots[i * 4].affine = AffineMatrix::IDENTITY.pa;
+      slots[i * 4 + 3].affine = AffineMatrix::IDENTITY.pd;
+      i += 1;
+    }
+    Self(slots)
+  }
+  #[inline]
+  pub const fn attr(&self, index: usize) -> ObjAttr {
+    self.0[index].attr
+  }
+  #[inline]
+  pub fn set_attr(&mut self, index: usize, attr: ObjAttr) {
+    self.0[index].attr = attr;
+  }
+  #[inline]
+  pub fn attr_mut(&mut self, index: usize) -> &mut ObjAttr {
+    &mut self.0[index].attr
+  }
+  /// Gets affine matrix `index`, `0..32`.
+  #[inline]
+  pub const fn affine(&self, index: usize) -> AffineMatrix {
+    AffineMatrix {
+      pa: self.0[index * 4].affine,
+      pb: self.0[index * 4 + 1].affine,
+      pc: self.0[index * 4 + 2].affine,
+      pd: self.0[index * 4 + 3].affine,
+    }
+  }
+  /// Sets affine matrix `index`, `0..32`.
+  #[inline]
+  pub fn set_affine(&mut self, index: usize, matrix: AffineMatrix) {
+    self.0[index * 4].affine = matrix.pa;
+    self.0[index * 4 + 1].affine = matrix.pb;
+    self.0[index * 4 + 2].affine = matrix.pc;
+    self.0[index * 4 + 3].affine = matrix.pd;
+  }
+  /// Hides object `n` and every object after it.
+  #[inline]
+  pub fn hide_from(&mut self, n: usize) {
+    for slot in self.0.iter_mut().skip(n) {
+      slot.attr = slot.attr.with_style(ObjDisplayStyle::NotDisplayed);
+    }
+  }
+  /// Copies the whole buffer into OAM.
+  ///
+  /// Call this during VBlank (or forced blank), since OAM can't be written
+  /// while the display is drawing objects.
+  #[inline]
+  pub fn commit(&self) {
+    let src = self as *const Self as *const u32;
+    let dest = OBJ_ATTRS.index(0).as_usize() as *mut u32;
+    let control = bios::CpuSetControl::new().with_count(256);
+    unsafe { bios::cpu_fast_set(src, dest, control) };
+  }
+}
+impl Default for OamShadow {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct Color(pub u16);
@@ -149,17 +925,207 @@ impl Color {
   }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+/// A signed 8.8 fixed point value.
+///
+/// Used for the affine matrix parameters.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 #[repr(transparent)]
-pub struct KeyInput(pub u16);
-#[rustfmt::skip]
-impl KeyInput {
-  #[inline]
-  pub const fn a(self) -> bool { !u16_get_bit(0, self.0) }
+pub struct I16F8(pub i16);
+impl I16F8 {
+  pub const ONE: Self = Self(1 << 8);
+
   #[inline]
-  pub const fn b(self) -> bool { !u16_get_bit(1, self.0) }
+  #[must_use]
+  pub const fn from_int(i: i16) -> Self {
+    Self(i << 8)
+  }
+  /// Rounds towards negative infinity.
   #[inline]
-  pub const fn select(self) -> bool { !u16_get_bit(2, self.0) }
+  #[must_use]
+  pub const fn to_int(self) -> i16 {
+    self.0 >> 8
+  }
+  #[inline]
+  #[must_use]
+  pub const fn from_bits(bits: i16) -> Self {
+    Self(bits)
+  }
+  #[inline]
+  #[must_use]
+  pub const fn to_bits(self) -> i16 {
+    self.0
+  }
+  #[inline]
+  #[must_use]
+  pub const fn mul(self, rhs: Self) -> Self {
+    Self(((self.0 as i32 * rhs.0 as i32) >> 8) as i16)
+  }
+}
+impl core::ops::Add for I16F8 {
+  type Output = Self;
+  #[inline]
+  fn add(self, rhs: Self) -> Self {
+    Self(self.0.wrapping_add(rhs.0))
+  }
+}
+impl core::ops::Sub for I16F8 {
+  type Output = Self;
+  #[inline]
+  fn sub(self, rhs: Self) -> Self {
+    Self(self.0.wrapping_sub(rhs.0))
+  }
+}
+impl core::ops::Neg for I16F8 {
+  type Output = Self;
+  #[inline]
+  fn neg(self) -> Self {
+    Self(self.0.wrapping_neg())
+  }
+}
+
+/// A signed fixed point value with 8 fractional bits, stored in an `i32`.
+///
+/// Used for the affine reference point, which has 19 bits of integer part (the
+/// hardware ignores the top 4 bits).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[repr(transparent)]
+pub struct I32F8(pub i32);
+impl I32F8 {
+  pub const ONE: Self = Self(1 << 8);
+
+  #[inline]
+  #[must_use]
+  pub const fn from_int(i: i32) -> Self {
+    Self(i << 8)
+  }
+  /// Rounds towards negative infinity.
+  #[inline]
+  #[must_use]
+  pub const fn to_int(self) -> i32 {
+    self.0 >> 8
+  }
+  #[inline]
+  #[must_use]
+  pub const fn from_bits(bits: i32) -> Self {
+    Self(bits)
+  }
+  #[inline]
+  #[must_use]
+  pub const fn to_bits(self) -> i32 {
+    self.0
+  }
+  #[inline]
+  #[must_use]
+  pub const fn mul(self, rhs: Self) -> Self {
+    Self(((self.0 as i64 * rhs.0 as i64) >> 8) as i32)
+  }
+}
+impl From<I16F8> for I32F8 {
+  #[inline]
+  fn from(f: I16F8) -> Self {
+    Self(f.0 as i32)
+  }
+}
+impl core::ops::Add for I32F8 {
+  type Output = Self;
+  #[inline]
+  fn add(self, rhs: Self) -> Self {
+    Self(self.0.wrapping_add(rhs.0))
+  }
+}
+impl core::ops::Sub for I32F8 {
+  type Output = Self;
+  #[inline]
+  fn sub(self, rhs: Self) -> Self {
+    Self(self.0.wrapping_sub(rhs.0))
+  }
+}
+impl core::ops::Neg for I32F8 {
+  type Output = Self;
+  #[inline]
+  fn neg(self) -> Self {
+    Self(self.0.wrapping_neg())
+  }
+}
+
+/// The state of the keys.
+///
+/// The hardware bits are "active low": a bit is 0 while that key is pressed.
+/// The constants here each have one key pressed, and combining them with `|`
+/// gives a value with all of those keys pressed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct KeyInput(pub u16);
+impl KeyInput {
+  pub const NONE: Self = Self(0x03FF);
+  pub const A: Self = Self::from_pressed(1 << 0);
+  pub const B: Self = Self::from_pressed(1 << 1);
+  pub const SELECT: Self = Self::from_pressed(1 << 2);
+  pub const START: Self = Self::from_pressed(1 << 3);
+  pub const RIGHT: Self = Self::from_pressed(1 << 4);
+  pub const LEFT: Self = Self::from_pressed(1 << 5);
+  pub const UP: Self = Self::from_pressed(1 << 6);
+  pub const DOWN: Self = Self::from_pressed(1 << 7);
+  pub const R: Self = Self::from_pressed(1 << 8);
+  pub const L: Self = Self::from_pressed(1 << 9);
+
+  /// Makes a value from "active high" bits, where 1 means pressed.
+  #[inline]
+  pub const fn from_pressed(pressed: u16) -> Self {
+    Self(!pressed & 0x03FF)
+  }
+  /// The "active high" bits, where 1 means pressed.
+  #[inline]
+  pub const fn pressed(self) -> u16 {
+    !self.0 & 0x03FF
+  }
+  /// If every key pressed in `other` is also pressed in `self`.
+  #[inline]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.pressed() & other.pressed()) == other.pressed()
+  }
+  /// If any key is pressed.
+  #[inline]
+  pub const fn any(self) -> bool {
+    self.pressed() != 0
+  }
+  /// Each pressed key, one at a time.
+  #[inline]
+  pub fn iter(self) -> impl Iterator<Item = Self> {
+    let pressed = self.pressed();
+    (0..10)
+      .filter(move |i| pressed & (1 << i) != 0)
+      .map(|i| Self::from_pressed(1 << i))
+  }
+}
+impl core::ops::BitOr for KeyInput {
+  type Output = Self;
+  /// All keys pressed in either value.
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    Self::from_pressed(self.pressed() | rhs.pressed())
+  }
+}
+impl core::ops::BitOrAssign for KeyInput {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    *self = *self | rhs;
+  }
+}
+impl Default for KeyInput {
+  #[inline]
+  fn default() -> Self {
+    Self::NONE
+  }
+}
+#[rustfmt::skip]
+impl KeyInput {
+  #[inline]
+  pub const fn a(self) -> bool { !u16_get_bit(0, self.0) }
+  #[inline]
+  pub const fn b(self) -> bool { !u16_get_bit(1, self.0) }
+  #[inline]
+  pub const fn select(self) -> bool { !u16_get_bit(2, self.0) }
   #[inline]
   pub const fn start(self) -> bool { !u16_get_bit(3, self.0) }
   #[inline]
@@ -176,6 +1142,64 @@ impl KeyInput {
   pub const fn l(self) -> bool { !u16_get_bit(9, self.0) }
 }
 
+/// Selects keys that send a keypad interrupt.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct KeyControl(u16);
+impl KeyControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// The keys to watch.
+  #[inline]
+  pub const fn with_keys(self, keys: KeyInput) -> Self {
+    Self(u16_with_value(0, 9, self.0, keys.pressed()))
+  }
+  #[inline]
+  pub const fn keys(self) -> KeyInput {
+    KeyInput::from_pressed(u16_get_value(0, 9, self.0))
+  }
+  #[inline]
+  pub const fn with_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(14, self.0, irq))
+  }
+  #[inline]
+  pub const fn irq(self) -> bool {
+    u16_get_bit(14, self.0)
+  }
+  /// If the interrupt needs *all* of the keys pressed at once, rather than
+  /// any one of them.
+  #[inline]
+  pub const fn with_all_keys(self, all: bool) -> Self {
+    Self(u16_with_bit(15, self.0, all))
+  }
+  #[inline]
+  pub const fn all_keys(self) -> bool {
+    u16_get_bit(15, self.0)
+  }
+}
+
+/// The background layout and pixel format used by the display.
+///
+/// * Mode 0: BG0-BG3 are all text backgrounds.
+/// * Mode 1: BG0 and BG1 are text, BG2 is affine.
+/// * Mode 2: BG2 and BG3 are affine.
+/// * Mode 3: BG2 is a single 240x160 bitmap of `Color` values.
+/// * Mode 4: BG2 is two 240x160 bitmaps of 8-bit palette indexes.
+/// * Mode 5: BG2 is two 160x128 bitmaps of `Color` values.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum VideoMode {
+  #[default]
+  Mode0 = 0,
+  Mode1 = 1,
+  Mode2 = 2,
+  Mode3 = 3,
+  Mode4 = 4,
+  Mode5 = 5,
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct DisplayControl(u16);
@@ -185,19 +1209,478 @@ impl DisplayControl {
     Self(0)
   }
   #[inline]
+  pub const fn with_video_mode(self, mode: VideoMode) -> Self {
+    Self(u16_with_value(0, 2, self.0, mode as u16))
+  }
+  #[inline]
+  pub const fn video_mode(self) -> VideoMode {
+    match u16_get_value(0, 2, self.0) {
+      0 => VideoMode::Mode0,
+      1 => VideoMode::Mode1,
+      2 => VideoMode::Mode2,
+      3 => VideoMode::Mode3,
+      4 => VideoMode::Mode4,
+      // 6 and 7 are invalid, and can't be set with `with_video_mode`
+      _ => VideoMode::Mode5,
+    }
+  }
+  /// This bit can only be set by the BIOS (when running GBC games), writes
+  /// from GBA code are ignored.
+  #[inline]
+  pub const fn with_cgb_mode(self, cgb: bool) -> Self {
+    Self(u16_with_bit(3, self.0, cgb))
+  }
+  #[inline]
+  pub const fn cgb_mode(self) -> bool {
+    u16_get_bit(3, self.0)
+  }
+  /// In modes 4 and 5, shows the second frame instead of the first.
+  #[inline]
+  pub const fn with_frame1(self, frame1: bool) -> Self {
+    Self(u16_with_bit(4, self.0, frame1))
+  }
+  #[inline]
+  pub const fn frame1(self) -> bool {
+    u16_get_bit(4, self.0)
+  }
+  /// Allows OAM access during HBlank, at the cost of fewer objects per line.
+  #[inline]
+  pub const fn with_hblank_oam_free(self, free: bool) -> Self {
+    Self(u16_with_bit(5, self.0, free))
+  }
+  #[inline]
+  pub const fn hblank_oam_free(self) -> bool {
+    u16_get_bit(5, self.0)
+  }
+  #[inline]
   pub const fn with_linear_obj_tiles(self, linear: bool) -> Self {
     Self(u16_with_bit(6, self.0, linear))
   }
   #[inline]
+  pub const fn linear_obj_tiles(self) -> bool {
+    u16_get_bit(6, self.0)
+  }
+  #[inline]
   pub const fn with_forced_blank(self, blank: bool) -> Self {
     Self(u16_with_bit(7, self.0, blank))
   }
   #[inline]
+  pub const fn forced_blank(self) -> bool {
+    u16_get_bit(7, self.0)
+  }
+  #[inline]
+  pub const fn with_bg0(self, bg0: bool) -> Self {
+    Self(u16_with_bit(8, self.0, bg0))
+  }
+  #[inline]
+  pub const fn bg0(self) -> bool {
+    u16_get_bit(8, self.0)
+  }
+  #[inline]
+  pub const fn with_bg1(self, bg1: bool) -> Self {
+    Self(u16_with_bit(9, self.0, bg1))
+  }
+  #[inline]
+  pub const fn bg1(self) -> bool {
+    u16_get_bit(9, self.0)
+  }
+  #[inline]
+  pub const fn with_bg2(self, bg2: bool) -> Self {
+    Self(u16_with_bit(10, self.0, bg2))
+  }
+  #[inline]
+  pub const fn bg2(self) -> bool {
+    u16_get_bit(10, self.0)
+  }
+  #[inline]
+  pub const fn with_bg3(self, bg3: bool) -> Self {
+    Self(u16_with_bit(11, self.0, bg3))
+  }
+  #[inline]
+  pub const fn bg3(self) -> bool {
+    u16_get_bit(11, self.0)
+  }
+  #[inline]
   pub const fn with_objects(self, objects: bool) -> Self {
     Self(u16_with_bit(12, self.0, objects))
   }
+  #[inline]
+  pub const fn objects(self) -> bool {
+    u16_get_bit(12, self.0)
+  }
+  #[inline]
+  pub const fn with_win0(self, win0: bool) -> Self {
+    Self(u16_with_bit(13, self.0, win0))
+  }
+  #[inline]
+  pub const fn win0(self) -> bool {
+    u16_get_bit(13, self.0)
+  }
+  #[inline]
+  pub const fn with_win1(self, win1: bool) -> Self {
+    Self(u16_with_bit(14, self.0, win1))
+  }
+  #[inline]
+  pub const fn win1(self) -> bool {
+    u16_get_bit(14, self.0)
+  }
+  #[inline]
+  pub const fn with_obj_window(self, obj_window: bool) -> Self {
+    Self(u16_with_bit(15, self.0, obj_window))
+  }
+  #[inline]
+  pub const fn obj_window(self) -> bool {
+    u16_get_bit(15, self.0)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct DisplayStatus(u16);
+impl DisplayStatus {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// If the display is in vertical blank (read only).
+  #[inline]
+  pub const fn is_vblank(self) -> bool {
+    u16_get_bit(0, self.0)
+  }
+  /// If the display is in horizontal blank (read only).
+  #[inline]
+  pub const fn is_hblank(self) -> bool {
+    u16_get_bit(1, self.0)
+  }
+  /// If `VCOUNT` matches the `vcount_setting` (read only).
+  #[inline]
+  pub const fn is_vcount_match(self) -> bool {
+    u16_get_bit(2, self.0)
+  }
+  #[inline]
+  pub const fn with_vblank_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(3, self.0, irq))
+  }
+  #[inline]
+  pub const fn vblank_irq(self) -> bool {
+    u16_get_bit(3, self.0)
+  }
+  #[inline]
+  pub const fn with_hblank_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(4, self.0, irq))
+  }
+  #[inline]
+  pub const fn hblank_irq(self) -> bool {
+    u16_get_bit(4, self.0)
+  }
+  #[inline]
+  pub const fn with_vcount_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(5, self.0, irq))
+  }
+  #[inline]
+  pub const fn vcount_irq(self) -> bool {
+    u16_get_bit(5, self.0)
+  }
+  /// The scanline that the VCount flag and interrupt trigger on.
+  #[inline]
+  pub const fn with_vcount_setting(self, line: u8) -> Self {
+    Self(u16_with_value(8, 15, self.0, line as u16))
+  }
+  #[inline]
+  pub const fn vcount_setting(self) -> u8 {
+    u16_get_value(8, 15, self.0) as u8
+  }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct BackgroundControl(u16);
+impl BackgroundControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// Draw priority, `0..=3`, with lower values drawn on top.
+  #[inline]
+  pub const fn with_priority(self, priority: u16) -> Self {
+    Self(u16_with_value(0, 1, self.0, priority))
+  }
+  #[inline]
+  pub const fn priority(self) -> u16 {
+    u16_get_value(0, 1, self.0)
+  }
+  /// The charblock, `0..=3`, that tile index 0 of the background uses.
+  #[inline]
+  pub const fn with_charblock(self, charblock: u16) -> Self {
+    Self(u16_with_value(2, 3, self.0, charblock))
+  }
+  #[inline]
+  pub const fn charblock(self) -> u16 {
+    u16_get_value(2, 3, self.0)
+  }
+  #[inline]
+  pub const fn with_mosaic(self, mosaic: bool) -> Self {
+    Self(u16_with_bit(6, self.0, mosaic))
+  }
+  #[inline]
+  pub const fn mosaic(self) -> bool {
+    u16_get_bit(6, self.0)
+  }
+  /// Use 8bpp tiles instead of 4bpp tiles (affine backgrounds are always
+  /// 8bpp).
+  #[inline]
+  pub const fn with_8bpp(self, bpp8: bool) -> Self {
+    Self(u16_with_bit(7, self.0, bpp8))
+  }
+  #[inline]
+  pub const fn is_8bpp(self) -> bool {
+    u16_get_bit(7, self.0)
+  }
+  /// The screenblock, `0..=31`, where the tilemap of the background starts.
+  #[inline]
+  pub const fn with_screenblock(self, screenblock: u16) -> Self {
+    Self(u16_with_value(8, 12, self.0, screenblock))
+  }
+  #[inline]
+  pub const fn screenblock(self) -> u16 {
+    u16_get_value(8, 12, self.0)
+  }
+  /// Affine backgrounds only: wrap around at the edges instead of showing
+  /// transparency.
+  #[inline]
+  pub const fn with_affine_wrap(self, wrap: bool) -> Self {
+    Self(u16_with_bit(13, self.0, wrap))
+  }
+  #[inline]
+  pub const fn affine_wrap(self) -> bool {
+    u16_get_bit(13, self.0)
+  }
+  /// The size of the background, `0..=3`.
+  ///
+  /// | Size | Text      | Affine    |
+  /// |:----:|:---------:|:---------:|
+  /// | 0    | 256x256   | 128x128   |
+  /// | 1    | 512x256   | 256x256   |
+  /// | 2    | 256x512   | 512x512   |
+  /// | 3    | 512x512   | 1024x1024 |
+  #[inline]
+  pub const fn with_size(self, size: u16) -> Self {
+    Self(u16_with_value(14, 15, self.0, size))
+  }
+  #[inline]
+  pub const fn size(self) -> u16 {
+    u16_get_value(14, 15, self.0)
+  }
+}
+
+/// Spins until the start of the next vertical blank.
+///
+/// If the display is already in vertical blank this waits for the *next* one.
+/// To wait without using the CPU, use
+/// [`vblank_intr_wait`](bios::vblank_intr_wait) instead.
+#[inline]
+pub fn wait_for_vblank_start() {
+  while VCOUNT.read() >= 160 {}
+  while VCOUNT.read() < 160 {}
+}
+
+/// Spins until the start of the next vertical draw (scanline 0).
+#[inline]
+pub fn wait_for_vdraw_start() {
+  while VCOUNT.read() < 160 {}
+  while VCOUNT.read() >= 160 {}
+}
+
+pub const IE: VolAddress<InterruptFlags, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0200) };
+
+pub const IF: VolAddress<InterruptFlags, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0202) };
+
+pub const IME: VolAddress<bool, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0208) };
+
+/// The BIOS "IntrCheck" flags, used by `IntrWait` and `VBlankIntrWait`.
+///
+/// The master IRQ handler sets the bits of each interrupt it handles.
+pub const INTR_CHECK: VolAddress<InterruptFlags, Safe, Safe> =
+  unsafe { VolAddress::new(0x0300_7FF8) };
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct InterruptFlags(u16);
+#[rustfmt::skip]
+impl InterruptFlags {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  #[inline]
+  pub const fn from_irq(irq: Irq) -> Self { Self(1 << irq as u16) }
+  #[inline]
+  pub const fn with_irq(self, irq: Irq, b: bool) -> Self { Self(u16_with_bit(irq as u32, self.0, b)) }
+  #[inline]
+  pub const fn irq(self, irq: Irq) -> bool { u16_get_bit(irq as u32, self.0) }
+  #[inline]
+  pub const fn with_vblank(self, b: bool) -> Self { Self(u16_with_bit(0, self.0, b)) }
+  #[inline]
+  pub const fn vblank(self) -> bool { u16_get_bit(0, self.0) }
+  #[inline]
+  pub const fn with_hblank(self, b: bool) -> Self { Self(u16_with_bit(1, self.0, b)) }
+  #[inline]
+  pub const fn hblank(self) -> bool { u16_get_bit(1, self.0) }
+  #[inline]
+  pub const fn with_vcount(self, b: bool) -> Self { Self(u16_with_bit(2, self.0, b)) }
+  #[inline]
+  pub const fn vcount(self) -> bool { u16_get_bit(2, self.0) }
+  #[inline]
+  pub const fn with_timer0(self, b: bool) -> Self { Self(u16_with_bit(3, self.0, b)) }
+  #[inline]
+  pub const fn timer0(self) -> bool { u16_get_bit(3, self.0) }
+  #[inline]
+  pub const fn with_timer1(self, b: bool) -> Self { Self(u16_with_bit(4, self.0, b)) }
+  #[inline]
+  pub const fn timer1(self) -> bool { u16_get_bit(4, self.0) }
+  #[inline]
+  pub const fn with_timer2(self, b: bool) -> Self { Self(u16_with_bit(5, self.0, b)) }
+  #[inline]
+  pub const fn timer2(self) -> bool { u16_get_bit(5, self.0) }
+  #[inline]
+  pub const fn with_timer3(self, b: bool) -> Self { Self(u16_with_bit(6, self.0, b)) }
+  #[inline]
+  pub const fn timer3(self) -> bool { u16_get_bit(6, self.0) }
+  #[inline]
+  pub const fn with_serial(self, b: bool) -> Self { Self(u16_with_bit(7, self.0, b)) }
+  #[inline]
+  pub const fn serial(self) -> bool { u16_get_bit(7, self.0) }
+  #[inline]
+  pub const fn with_dma0(self, b: bool) -> Self { Self(u16_with_bit(8, self.0, b)) }
+  #[inline]
+  pub const fn dma0(self) -> bool { u16_get_bit(8, self.0) }
+  #[inline]
+  pub const fn with_dma1(self, b: bool) -> Self { Self(u16_with_bit(9, self.0, b)) }
+  #[inline]
+  pub const fn dma1(self) -> bool { u16_get_bit(9, self.0) }
+  #[inline]
+  pub const fn with_dma2(self, b: bool) -> Self { Self(u16_with_bit(10, self.0, b)) }
+  #[inline]
+  pub const fn dma2(self) -> bool { u16_get_bit(10, self.0) }
+  #[inline]
+  pub const fn with_dma3(self, b: bool) -> Self { Self(u16_with_bit(11, self.0, b)) }
+  #[inline]
+  pub const fn dma3(self) -> bool { u16_get_bit(11, self.0) }
+  #[inline]
+  pub const fn with_keypad(self, b: bool) -> Self { Self(u16_with_bit(12, self.0, b)) }
+  #[inline]
+  pub const fn keypad(self) -> bool { u16_get_bit(12, self.0) }
+  #[inline]
+  pub const fn with_gamepak(self, b: bool) -> Self { Self(u16_with_bit(13, self.0, b)) }
+  #[inline]
+  pub const fn gamepak(self) -> bool { u16_get_bit(13, self.0) }
+}
+
+/// The sources of hardware interrupts, in `IE` / `IF` bit order.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum Irq {
+  VBlank = 0,
+  HBlank = 1,
+  VCount = 2,
+  Timer0 = 3,
+  Timer1 = 4,
+  Timer2 = 5,
+  Timer3 = 6,
+  Serial = 7,
+  Dma0 = 8,
+  Dma1 = 9,
+  Dma2 = 10,
+  Dma3 = 11,
+  Keypad = 12,
+  GamePak = 13,
+}
+
+static mut IRQ_HANDLERS: [Option<fn()>; 14] = [None; 14];
+
+/// Sets the function to call when the given interrupt fires.
+///
+/// The master IRQ handler (installed by `_start`) acknowledges the interrupt
+/// in both `IF` and [`INTR_CHECK`] and then calls this handler, if any. The
+/// handler runs with interrupts disabled, on the normal user stack.
+///
+/// This does not touch `IE` or `IME`, or the interrupt enable bits of the
+/// device registers, so the interrupt must still be enabled separately.
+///
+/// Returns the previous handler, so that it can be put back later.
+pub fn set_irq_handler(irq: Irq, handler: Option<fn()>) -> Option<fn()> {
+  let ime = IME.read();
+  IME.write(false);
+  let old =
+    unsafe { core::mem::replace(&mut IRQ_HANDLERS[irq as usize], handler) };
+  IME.write(ime);
+  old
+}
+
+extern "C" fn irq_dispatch(flags: InterruptFlags) {
+  let mut bits = flags.0;
+  while bits != 0 {
+    let i = bits.trailing_zeros() as usize;
+    bits &= bits - 1;
+    if let Some(handler) = unsafe { IRQ_HANDLERS[i] } {
+      handler();
+    }
+  }
+}
+
+/// The master IRQ handler, which the BIOS calls in IRQ mode.
+#[naked]
+#[instruction_set(arm::a32)]
+#[link_section = ".iwram.text.irq_handler"]
+unsafe extern "C" fn irq_handler() {
+  core::arch::asm! {
+    // r0 = IE & IF
+    "mov r12, #0x04000000",
+    "add r12, r12, #0x200",
+    "ldr r0, [r12]",
+    "and r0, r0, r0, lsr #16",
+
+    // acknowledge the interrupts in IF and the BIOS IntrCheck
+    "strh r0, [r12, #2]",
+    "ldr r2, =0x03007FF8",
+    "ldrh r1, [r2]",
+    "orr r1, r1, r0",
+    "strh r1, [r2]",
+
+    // switch to System mode so the Rust code uses the user stack
+    "mrs r2, cpsr",
+    "orr r2, r2, #0xD",
+    "msr cpsr_c, r2",
+    "mov r3, sp",
+    "bic sp, sp, #7",
+    "push {{r3, lr}}",
+
+    "ldr r1, ={dispatch}",
+    "mov lr, pc",
+    "bx r1",
+
+    // switch back to IRQ mode and return to the BIOS
+    "pop {{r3, lr}}",
+    "mov sp, r3",
+    "mrs r2, cpsr",
+    "bic r2, r2, #0xD",
+    "msr cpsr_c, r2",
+    "bx lr",
+    dispatch = sym irq_dispatch,
+    options(noreturn)
+  }
+}
+
+/// The entry point of the program.
+///
+/// Before calling `main` this sets up the runtime memory:
+/// * `.data` and `.iwram` (including any `.iwram.text` code) are copied from
+///   ROM into IWRAM.
+/// * `.ewram` is copied from ROM into EWRAM.
+/// * `.bss` is zeroed.
+///
+/// It also installs the master IRQ handler.
 #[naked]
 #[no_mangle]
 #[instruction_set(arm::a32)]
@@ -207,8 +1690,44 @@ unsafe extern "C" fn _start() -> ! {
     "b 1f",
     ".space 0xE0",
     "1:",
+
+    // copy the IWRAM data
+    "ldr r0, =__iwram_position_in_rom",
+    "ldr r1, =__iwram_start",
+    "ldr r2, =__iwram_end",
+    "2:",
+    "cmp r1, r2",
+    "ldrlt r3, [r0], #4",
+    "strlt r3, [r1], #4",
+    "blt 2b",
+
+    // copy the EWRAM data
+    "ldr r0, =__ewram_position_in_rom",
+    "ldr r1, =__ewram_start",
+    "ldr r2, =__ewram_end",
+    "3:",
+    "cmp r1, r2",
+    "ldrlt r3, [r0], #4",
+    "strlt r3, [r1], #4",
+    "blt 3b",
+
+    // zero the BSS
+    "ldr r1, =__bss_start",
+    "ldr r2, =__bss_end",
+    "mov r3, #0",
+    "4:",
+    "cmp r1, r2",
+    "strlt r3, [r1], #4",
+    "blt 4b",
+
+    // install the master IRQ handler
+    "ldr r0, =0x03007FFC",
+    "ldr r1, ={irq_handler}",
+    "str r1, [r0]",
+
     "ldr r12, =main",
     "bx r12",
+    irq_handler = sym irq_handler,
     options(noreturn)
   }
 }
@@ -0,0 +1,277 @@
+//! Debug logging to emulators.
+//!
+//! Two emulators are supported, each with a [`LogBackend`]:
+//!
+//! * [`Mgba`]: mGBA has a few extra IO registers for debug output. Writing
+//!   `0xC0DE` to the enable register turns them on, after which it reads as
+//!   `0x1DEA`. A message is written into the buffer (up to 256 bytes) and then
+//!   sent by writing its level (plus `0x100`) to the flags register.
+//! * [`NoCashGba`]: no$gba prints any message embedded in the code after a `mov
+//!   r12, r12` instruction, when followed by a branch over the `0x6464`
+//!   signature and the message text. The message is written into just such a
+//!   function, which lives in IWRAM so that it can be modified.
+//!
+//! The emulator is detected at runtime (see [`detect_emulator`]), so one ROM
+//! logs correctly under either. The [`log!`](crate::log!),
+//! [`error!`](crate::error!), [`warn!`](crate::warn!), [`info!`](crate::info!),
+//! and [`debug!`](crate::debug!) macros check for an emulator before doing any
+//! formatting, so they're cheap when running anywhere else.
+
+use core::fmt;
+
+use voladdress::{Safe, VolAddress, VolBlock};
+
+pub const MGBA_LOG_ENABLE: VolAddress<u16, Safe, Safe> =
+  unsafe { VolAddress::new(0x04FF_F780) };
+pub const MGBA_LOG_FLAGS: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x04FF_F700) };
+pub const MGBA_LOG_BUFFER: VolBlock<u8, (), Safe, 256> =
+  unsafe { VolBlock::new(0x04FF_F600) };
+
+/// Reads as `"no$gba"` (followed by the version) when running in no$gba.
+pub const NOCASH_SIGNATURE: VolBlock<u8, Safe, (), 16> =
+  unsafe { VolBlock::new(0x04FF_FA00) };
+
+/// How important a log message is.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[repr(u16)]
+pub enum LogLevel {
+  /// mGBA shows fatal messages in a popup and stops the game.
+  Fatal = 0,
+  Error = 1,
+  Warn = 2,
+  Info = 3,
+  Debug = 4,
+}
+
+/// An emulator that logging can be sent to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Emulator {
+  Mgba,
+  NoCashGba,
+  /// Real hardware, or an emulator without debug output.
+  Unknown,
+}
+
+/// `0` is unchecked, otherwise it's `1` plus the `Emulator`.
+static mut EMULATOR_STATE: u8 = 0;
+
+/// Finds out which emulator (if any) is running the game.
+///
+/// The check is only done once, and then the result is cached.
+#[inline]
+pub fn detect_emulator() -> Emulator {
+  unsafe {
+    if EMULATOR_STATE == 0 {
+      let emulator = if NOCASH_SIGNATURE
+        .iter()
+        .zip(b"no$gba")
+        .all(|(a, b)| a.read() == *b)
+      {
+        Emulator::NoCashGba
+      } else {
+        MGBA_LOG_ENABLE.write(0xC0DE);
+        if MGBA_LOG_ENABLE.read() == 0x1DEA {
+          Emulator::Mgba
+        } else {
+          Emulator::Unknown
+        }
+      };
+      EMULATOR_STATE = 1 + emulator as u8;
+    }
+    match EMULATOR_STATE {
+      1 => Emulator::Mgba,
+      2 => Emulator::NoCashGba,
+      _ => Emulator::Unknown,
+    }
+  }
+}
+
+/// A way to send debug messages.
+pub trait LogBackend {
+  /// The most bytes that one message can hold.
+  const MAX_LEN: usize;
+  /// Sets byte `i` of the next message.
+  fn write_byte(i: usize, b: u8);
+  /// Sends the first `len` bytes as a message.
+  fn send(len: usize, level: LogLevel);
+}
+
+/// The mGBA debug registers.
+pub struct Mgba;
+impl LogBackend for Mgba {
+  const MAX_LEN: usize = 255;
+  #[inline]
+  fn write_byte(i: usize, b: u8) {
+    MGBA_LOG_BUFFER.index(i).write(b);
+  }
+  #[inline]
+  fn send(len: usize, level: LogLevel) {
+    MGBA_LOG_BUFFER.index(len).write(0);
+    MGBA_LOG_FLAGS.write(level as u16 | 0x100);
+  }
+}
+
+/// The no$gba debug message function.
+///
+/// no$gba doesn't have log levels, so the level is ignored.
+pub struct NoCashGba;
+impl LogBackend for NoCashGba {
+  const MAX_LEN: usize = 80;
+  #[inline]
+  fn write_byte(i: usize, b: u8) {
+    assert!(i < Self::MAX_LEN);
+    unsafe { nocash_msg_buffer().add(i).write_volatile(b) };
+  }
+  #[inline]
+  fn send(len: usize, _level: LogLevel) {
+    unsafe { nocash_msg_buffer().add(len).write_volatile(0) };
+    // The function is in IWRAM, which is too far from ROM for a direct call.
+    let f = core::hint::black_box(nocash_print as unsafe extern "C" fn());
+    unsafe { f() }
+  }
+}
+
+/// The message text inside of [`nocash_print`], with room for
+/// [`NoCashGba::MAX_LEN`] bytes plus a null terminator.
+#[inline]
+fn nocash_msg_buffer() -> *mut u8 {
+  // Skip the thumb bit, and the 8 bytes of code before the message.
+  ((nocash_print as usize & !1) + 8) as *mut u8
+}
+
+/// Prints the message buffer in no$gba (and does nothing elsewhere).
+#[naked]
+#[instruction_set(arm::t32)]
+#[link_section = ".iwram.text.nocash_print"]
+unsafe extern "C" fn nocash_print() {
+  core::arch::asm! {
+    "mov r12, r12",
+    "b 1f",
+    ".hword 0x6464",
+    ".hword 0",
+    ".space 82",
+    "1:",
+    "bx lr",
+    options(noreturn)
+  }
+}
+
+/// Formats text into messages for a [`LogBackend`].
+///
+/// Each line of the output becomes its own message, as does each
+/// [`MAX_LEN`](LogBackend::MAX_LEN) bytes of a long line. Anything not yet
+/// sent is sent when the writer is dropped.
+pub struct LogWriter<B: LogBackend> {
+  level: LogLevel,
+  len: usize,
+  backend: core::marker::PhantomData<B>,
+}
+impl<B: LogBackend> LogWriter<B> {
+  #[inline]
+  pub const fn new(level: LogLevel) -> Self {
+    Self { level, len: 0, backend: core::marker::PhantomData }
+  }
+
+  /// Sends the current buffer as a message.
+  #[inline]
+  pub fn flush(&mut self) {
+    B::send(self.len, self.level);
+    self.len = 0;
+  }
+}
+impl<B: LogBackend> fmt::Write for LogWriter<B> {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for b in s.bytes() {
+      if b == b'\n' {
+        self.flush();
+        continue;
+      }
+      B::write_byte(self.len, b);
+      self.len += 1;
+      if self.len == B::MAX_LEN {
+        self.flush();
+      }
+    }
+    Ok(())
+  }
+}
+impl<B: LogBackend> Drop for LogWriter<B> {
+  #[inline]
+  fn drop(&mut self) {
+    if self.len > 0 {
+      self.flush();
+    }
+  }
+}
+
+/// Writes to whichever emulator was detected.
+pub enum Logger {
+  Mgba(LogWriter<Mgba>),
+  NoCashGba(LogWriter<NoCashGba>),
+}
+impl Logger {
+  /// Makes a logger, or `None` if no emulator with debug output is detected.
+  #[inline]
+  pub fn new(level: LogLevel) -> Option<Self> {
+    match detect_emulator() {
+      Emulator::Mgba => Some(Self::Mgba(LogWriter::new(level))),
+      Emulator::NoCashGba => Some(Self::NoCashGba(LogWriter::new(level))),
+      Emulator::Unknown => None,
+    }
+  }
+}
+impl fmt::Write for Logger {
+  #[inline]
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    match self {
+      Self::Mgba(w) => w.write_str(s),
+      Self::NoCashGba(w) => w.write_str(s),
+    }
+  }
+}
+
+/// Logs a message at the given [`LogLevel`], using `format_args!` syntax.
+///
+/// Does nothing (not even the formatting) if no debug output is available.
+#[macro_export]
+macro_rules! log {
+  ($level:expr, $($arg:tt)*) => {{
+    if let Some(mut w) = $crate::log::Logger::new($level) {
+      use core::fmt::Write;
+      let _ = write!(w, $($arg)*);
+    }
+  }};
+}
+
+/// Logs at [`LogLevel::Error`](crate::log::LogLevel::Error).
+#[macro_export]
+macro_rules! error {
+  ($($arg:tt)*) => {
+    $crate::log!($crate::log::LogLevel::Error, $($arg)*)
+  };
+}
+
+/// Logs at [`LogLevel::Warn`](crate::log::LogLevel::Warn).
+#[macro_export]
+macro_rules! warn {
+  ($($arg:tt)*) => {
+    $crate::log!($crate::log::LogLevel::Warn, $($arg)*)
+  };
+}
+
+/// Logs at [`LogLevel::Info`](crate::log::LogLevel::Info).
+#[macro_export]
+macro_rules! info {
+  ($($arg:tt)*) => {
+    $crate::log!($crate::log::LogLevel::Info, $($arg)*)
+  };
+}
+
+/// Logs at [`LogLevel::Debug`](crate::log::LogLevel::Debug).
+#[macro_export]
+macro_rules! debug {
+  ($($arg:tt)*) => {
+    $crate::log!($crate::log::LogLevel::Debug, $($arg)*)
+  };
+}
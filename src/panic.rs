@@ -0,0 +1,198 @@
+//! A panic handler that shows the panic on screen.
+//!
+//! Enabled with the `panic_handler` cargo feature. The panic is sent to the
+//! debug log (if any), and then the display is switched to a text background
+//! showing the message and location, so that panics can also be read on real
+//! hardware.
+//!
+//! A program that doesn't otherwise use anything from this crate still needs
+//! to link it for the handler to be found: `use gba_from_scratch as _;`
+
+use core::{fmt, panic::PanicInfo};
+
+use crate::{
+  bios::{self, BitUnpackInfo},
+  dma::{DmaControl, DMA0_CONTROL, DMA1_CONTROL, DMA2_CONTROL, DMA3_CONTROL},
+  log::LogLevel,
+  BackgroundControl, Color, DisplayControl, InterruptFlags, TextEntry,
+  VideoMode, BG0CNT, BG0HOFS, BG0VOFS, BG_PALETTE, CHARBLOCK0_TILE4, DISPCNT,
+  IE, IME, SCREENBLOCK31,
+};
+
+/// 1bpp glyphs for ASCII `0x20..=0x7F`, 8 bytes per glyph, with the lowest bit
+/// of each byte as the leftmost pixel.
+///
+/// From the X11 "misc-fixed" 5x8 font, which is in the public domain.
+#[rustfmt::skip]
+static FONT: [u8; 96 * 8] = [
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ' '
+  0x00, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00, // '!'
+  0x00, 0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, // '"'
+  0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A, 0x00, // '#'
+  0x04, 0x0E, 0x05, 0x0E, 0x14, 0x0E, 0x04, 0x00, // '$'
+  0x00, 0x02, 0x0A, 0x04, 0x0A, 0x08, 0x00, 0x00, // '%'
+  0x02, 0x05, 0x05, 0x02, 0x05, 0x05, 0x0A, 0x00, // '&'
+  0x00, 0x04, 0x04, 0x04, 0x00, 0x00, 0x00, 0x00, // "'"
+  0x00, 0x04, 0x02, 0x02, 0x02, 0x02, 0x04, 0x00, // '('
+  0x00, 0x02, 0x04, 0x04, 0x04, 0x04, 0x02, 0x00, // ')'
+  0x00, 0x00, 0x09, 0x06, 0x0F, 0x06, 0x09, 0x00, // '*'
+  0x00, 0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, // '+'
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x02, // ','
+  0x00, 0x00, 0x00, 0x00, 0x0F, 0x00, 0x00, 0x00, // '-'
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x0E, 0x04, // '.'
+  0x00, 0x08, 0x08, 0x04, 0x02, 0x01, 0x01, 0x00, // '/'
+  0x00, 0x04, 0x0A, 0x0A, 0x0A, 0x0A, 0x04, 0x00, // '0'
+  0x00, 0x04, 0x06, 0x04, 0x04, 0x04, 0x0E, 0x00, // '1'
+  0x00, 0x06, 0x09, 0x08, 0x06, 0x01, 0x0F, 0x00, // '2'
+  0x00, 0x0F, 0x04, 0x06, 0x08, 0x09, 0x06, 0x00, // '3'
+  0x00, 0x04, 0x06, 0x05, 0x0F, 0x04, 0x04, 0x00, // '4'
+  0x00, 0x0F, 0x01, 0x07, 0x08, 0x09, 0x06, 0x00, // '5'
+  0x00, 0x06, 0x01, 0x07, 0x09, 0x09, 0x06, 0x00, // '6'
+  0x00, 0x0F, 0x08, 0x04, 0x04, 0x02, 0x02, 0x00, // '7'
+  0x00, 0x06, 0x09, 0x06, 0x09, 0x09, 0x06, 0x00, // '8'
+  0x00, 0x06, 0x09, 0x09, 0x0E, 0x08, 0x06, 0x00, // '9'
+  0x00, 0x00, 0x06, 0x06, 0x00, 0x06, 0x06, 0x00, // ':'
+  0x00, 0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x02, // ';'
+  0x00, 0x08, 0x04, 0x02, 0x02, 0x04, 0x08, 0x00, // '<'
+  0x00, 0x00, 0x00, 0x0F, 0x00, 0x0F, 0x00, 0x00, // '='
+  0x00, 0x02, 0x04, 0x08, 0x08, 0x04, 0x02, 0x00, // '>'
+  0x00, 0x04, 0x0A, 0x08, 0x04, 0x00, 0x04, 0x00, // '?'
+  0x0C, 0x12, 0x19, 0x15, 0x15, 0x09, 0x02, 0x0C, // '@'
+  0x00, 0x06, 0x09, 0x09, 0x0F, 0x09, 0x09, 0x00, // 'A'
+  0x00, 0x07, 0x09, 0x07, 0x09, 0x09, 0x07, 0x00, // 'B'
+  0x00, 0x06, 0x09, 0x01, 0x01, 0x09, 0x06, 0x00, // 'C'
+  0x00, 0x07, 0x09, 0x09, 0x09, 0x09, 0x07, 0x00, // 'D'
+  0x00, 0x0F, 0x01, 0x07, 0x01, 0x01, 0x0F, 0x00, // 'E'
+  0x00, 0x0F, 0x01, 0x07, 0x01, 0x01, 0x01, 0x00, // 'F'
+  0x00, 0x06, 0x09, 0x01, 0x0D, 0x09, 0x06, 0x00, // 'G'
+  0x00, 0x09, 0x09, 0x0F, 0x09, 0x09, 0x09, 0x00, // 'H'
+  0x00, 0x0E, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00, // 'I'
+  0x00, 0x0E, 0x04, 0x04, 0x04, 0x05, 0x02, 0x00, // 'J'
+  0x00, 0x09, 0x05, 0x03, 0x05, 0x05, 0x09, 0x00, // 'K'
+  0x00, 0x01, 0x01, 0x01, 0x01, 0x01, 0x0F, 0x00, // 'L'
+  0x00, 0x09, 0x0F, 0x0F, 0x09, 0x09, 0x09, 0x00, // 'M'
+  0x00, 0x09, 0x0B, 0x0F, 0x0D, 0x0D, 0x09, 0x00, // 'N'
+  0x00, 0x06, 0x09, 0x09, 0x09, 0x09, 0x06, 0x00, // 'O'
+  0x00, 0x07, 0x09, 0x09, 0x07, 0x01, 0x01, 0x00, // 'P'
+  0x00, 0x06, 0x09, 0x09, 0x0B, 0x0D, 0x06, 0x08, // 'Q'
+  0x00, 0x07, 0x09, 0x09, 0x07, 0x09, 0x09, 0x00, // 'R'
+  0x00, 0x06, 0x09, 0x02, 0x04, 0x09, 0x06, 0x00, // 'S'
+  0x00, 0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00, // 'T'
+  0x00, 0x09, 0x09, 0x09, 0x09, 0x09, 0x06, 0x00, // 'U'
+  0x00, 0x09, 0x09, 0x09, 0x09, 0x06, 0x06, 0x00, // 'V'
+  0x00, 0x09, 0x09, 0x09, 0x0F, 0x0F, 0x09, 0x00, // 'W'
+  0x00, 0x09, 0x09, 0x06, 0x06, 0x09, 0x09, 0x00, // 'X'
+  0x00, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x00, // 'Y'
+  0x00, 0x0F, 0x08, 0x04, 0x02, 0x01, 0x0F, 0x00, // 'Z'
+  0x00, 0x0E, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00, // '['
+  0x00, 0x01, 0x01, 0x02, 0x04, 0x08, 0x08, 0x00, // '\\'
+  0x00, 0x0E, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00, // ']'
+  0x00, 0x04, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00, // '^'
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0F, // '_'
+  0x00, 0x02, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, // '`'
+  0x00, 0x00, 0x00, 0x0E, 0x09, 0x09, 0x0E, 0x00, // 'a'
+  0x00, 0x01, 0x01, 0x07, 0x09, 0x09, 0x07, 0x00, // 'b'
+  0x00, 0x00, 0x00, 0x0C, 0x02, 0x02, 0x0C, 0x00, // 'c'
+  0x00, 0x08, 0x08, 0x0E, 0x09, 0x09, 0x0E, 0x00, // 'd'
+  0x00, 0x00, 0x00, 0x06, 0x0D, 0x03, 0x06, 0x00, // 'e'
+  0x00, 0x04, 0x0A, 0x02, 0x07, 0x02, 0x02, 0x00, // 'f'
+  0x00, 0x00, 0x00, 0x06, 0x09, 0x0E, 0x08, 0x06, // 'g'
+  0x00, 0x01, 0x01, 0x07, 0x09, 0x09, 0x09, 0x00, // 'h'
+  0x00, 0x04, 0x00, 0x06, 0x04, 0x04, 0x0E, 0x00, // 'i'
+  0x00, 0x08, 0x00, 0x08, 0x08, 0x08, 0x0A, 0x04, // 'j'
+  0x00, 0x01, 0x01, 0x09, 0x07, 0x09, 0x09, 0x00, // 'k'
+  0x00, 0x06, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00, // 'l'
+  0x00, 0x00, 0x00, 0x0B, 0x15, 0x15, 0x15, 0x00, // 'm'
+  0x00, 0x00, 0x00, 0x07, 0x09, 0x09, 0x09, 0x00, // 'n'
+  0x00, 0x00, 0x00, 0x06, 0x09, 0x09, 0x06, 0x00, // 'o'
+  0x00, 0x00, 0x00, 0x07, 0x09, 0x07, 0x01, 0x01, // 'p'
+  0x00, 0x00, 0x00, 0x0E, 0x09, 0x0E, 0x08, 0x08, // 'q'
+  0x00, 0x00, 0x00, 0x05, 0x0B, 0x01, 0x01, 0x00, // 'r'
+  0x00, 0x00, 0x00, 0x0C, 0x06, 0x08, 0x06, 0x00, // 's'
+  0x00, 0x02, 0x02, 0x07, 0x02, 0x0A, 0x04, 0x00, // 't'
+  0x00, 0x00, 0x00, 0x09, 0x09, 0x09, 0x0E, 0x00, // 'u'
+  0x00, 0x00, 0x00, 0x0A, 0x0A, 0x0A, 0x04, 0x00, // 'v'
+  0x00, 0x00, 0x00, 0x11, 0x15, 0x15, 0x0A, 0x00, // 'w'
+  0x00, 0x00, 0x00, 0x09, 0x06, 0x06, 0x09, 0x00, // 'x'
+  0x00, 0x00, 0x00, 0x09, 0x09, 0x0E, 0x09, 0x06, // 'y'
+  0x00, 0x00, 0x00, 0x0F, 0x04, 0x02, 0x0F, 0x00, // 'z'
+  0x0C, 0x02, 0x04, 0x03, 0x04, 0x02, 0x0C, 0x00, // '{'
+  0x00, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00, // '|'
+  0x03, 0x04, 0x02, 0x0C, 0x02, 0x04, 0x03, 0x00, // '}'
+  0x00, 0x0A, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, // '~'
+  0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // 'DEL'
+];
+
+/// Writes text into `SCREENBLOCK31`, wrapping at the edge of the screen.
+struct ScreenWriter {
+  col: usize,
+  row: usize,
+}
+impl fmt::Write for ScreenWriter {
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for c in s.chars() {
+      if self.row >= 20 {
+        break;
+      }
+      if c == '\n' {
+        self.col = 0;
+        self.row += 1;
+        continue;
+      }
+      let tile = if (' '..='~').contains(&c) { c as u16 } else { b'?' as u16 };
+      SCREENBLOCK31
+        .index(self.row * 32 + self.col)
+        .write(TextEntry::new().with_tile(tile));
+      self.col += 1;
+      if self.col == 30 {
+        self.col = 0;
+        self.row += 1;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[panic_handler]
+fn panic_handler(info: &PanicInfo) -> ! {
+  IME.write(false);
+  IE.write(InterruptFlags::new());
+  // A raster effect or other repeating DMA could mess with the display.
+  for control in [DMA0_CONTROL, DMA1_CONTROL, DMA2_CONTROL, DMA3_CONTROL] {
+    unsafe { control.write(DmaControl::new()) };
+  }
+
+  crate::log!(LogLevel::Error, "{info}");
+
+  DISPCNT.write(DisplayControl::new().with_forced_blank(true));
+  BG_PALETTE.index(0).write(Color::rgb(0, 0, 12));
+  BG_PALETTE.index(1).write(Color::WHITE);
+  CHARBLOCK0_TILE4.index(0).write([0; 8]);
+  let unpack = BitUnpackInfo {
+    src_len: FONT.len() as u16,
+    src_width: 1,
+    dest_width: 4,
+    offset: 0,
+  };
+  unsafe {
+    let dest = CHARBLOCK0_TILE4.index(0x20).as_usize() as *mut u32;
+    bios::bit_unpack(FONT.as_ptr(), dest, &unpack);
+  }
+  SCREENBLOCK31.iter().for_each(|a| a.write(TextEntry::new()));
+  BG0HOFS.write(0);
+  BG0VOFS.write(0);
+  BG0CNT.write(BackgroundControl::new().with_charblock(0).with_screenblock(31));
+
+  let _ = fmt::Write::write_fmt(
+    &mut ScreenWriter { col: 0, row: 0 },
+    format_args!("{info}"),
+  );
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::Mode0).with_bg0(true),
+  );
+
+  loop {
+    // With no interrupts enabled, this halts forever.
+    bios::halt();
+  }
+}
@@ -0,0 +1,161 @@
+//! Cycle counting, using timers 2 and 3.
+//!
+//! Timer 2 counts every CPU cycle, and timer 3 cascades off of it, giving a
+//! 32-bit cycle count (about 4 minutes before it wraps). Don't use timers 2 and
+//! 3 for anything else while profiling.
+//!
+//! Starting a new [`Stopwatch`] (including via [`profile`] or
+//! [`RegionProfiler::reset`]) restarts the shared counter, so don't nest them
+//! inside each other. Regions of a [`RegionProfiler`] *can* be nested, since
+//! [`region`](RegionProfiler::region) only needs a shared reference.
+
+use core::{cell::Cell, fmt};
+
+use crate::timer::{
+  Prescaler, Timer, TimerControl, TIMER2_COUNT, TIMER3_COUNT,
+};
+
+/// Reads the 32-bit count of timers 2 and 3.
+#[inline]
+fn read_cycles() -> u32 {
+  loop {
+    let high = TIMER3_COUNT.read();
+    let low = TIMER2_COUNT.read();
+    // If the low half overflowed between the reads, try again.
+    if TIMER3_COUNT.read() == high {
+      return ((high as u32) << 16) | (low as u32);
+    }
+  }
+}
+
+/// Counts CPU cycles since it was started.
+pub struct Stopwatch(());
+impl Stopwatch {
+  /// Restarts timers 2 and 3 from 0.
+  #[inline]
+  pub fn start() -> Self {
+    Timer::TIMER2.stop();
+    Timer::TIMER3.stop();
+    Timer::TIMER2.set_reload(0);
+    Timer::TIMER3.set_reload(0);
+    Timer::TIMER3.start(TimerControl::new().with_cascade(true));
+    Timer::TIMER2.start(TimerControl::new().with_prescaler(Prescaler::Div1));
+    Self(())
+  }
+  /// Cycles since the stopwatch started.
+  #[inline]
+  pub fn elapsed(&self) -> u32 {
+    read_cycles()
+  }
+  /// Stops timers 2 and 3, giving the final cycle count.
+  #[inline]
+  pub fn stop(self) -> u32 {
+    Timer::TIMER2.stop();
+    Timer::TIMER3.stop();
+    read_cycles()
+  }
+}
+
+/// Runs `f`, returning how many cycles it took.
+///
+/// The count includes a few cycles of overhead for starting and stopping the
+/// timers.
+#[inline]
+pub fn profile(f: impl FnOnce()) -> u32 {
+  let watch = Stopwatch::start();
+  f();
+  watch.stop()
+}
+
+#[derive(Clone, Copy)]
+struct Region {
+  name: &'static str,
+  cycles: u32,
+  calls: u32,
+}
+
+/// Totals up the cycles spent in named regions of code.
+///
+/// Call [`reset`](Self::reset) at the start of each frame, wrap the code to
+/// measure in [`region`](Self::region), and [`dump`](Self::dump) the results.
+/// Up to `N` different names are tracked, and regions with names past that are
+/// run without being measured.
+///
+/// A nested region's cycles are counted in its own total and also in the
+/// total of every region around it.
+pub struct RegionProfiler<const N: usize> {
+  regions: [Cell<Region>; N],
+  len: Cell<usize>,
+  watch: Option<Stopwatch>,
+}
+impl<const N: usize> RegionProfiler<N> {
+  // Only used to fill the array in `new`, which makes a fresh copy each time.
+  #[allow(clippy::declare_interior_mutable_const)]
+  const EMPTY: Cell<Region> =
+    Cell::new(Region { name: "", cycles: 0, calls: 0 });
+
+  #[inline]
+  pub const fn new() -> Self {
+    Self { regions: [Self::EMPTY; N], len: Cell::new(0), watch: None }
+  }
+
+  /// Clears all totals and restarts the cycle counter.
+  #[inline]
+  pub fn reset(&mut self) {
+    self.len.set(0);
+    self.watch = Some(Stopwatch::start());
+  }
+
+  /// Runs `f`, adding the cycles it took to the total for `name`.
+  ///
+  /// Does nothing extra if [`reset`](Self::reset) hasn't been called yet.
+  #[inline]
+  pub fn region<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+    let Some(watch) = &self.watch else { return f() };
+    let start = watch.elapsed();
+    let output = f();
+    let cycles = watch.elapsed().wrapping_sub(start);
+    self.add(name, cycles);
+    output
+  }
+
+  /// The regions seen since the last reset.
+  #[inline]
+  fn seen(&self) -> impl Iterator<Item = Region> + '_ {
+    self.regions[..self.len.get()].iter().map(Cell::get)
+  }
+
+  fn add(&self, name: &'static str, cycles: u32) {
+    let len = self.len.get();
+    let regions = &self.regions[..len];
+    if let Some(cell) = regions.iter().find(|r| r.get().name == name) {
+      let mut region = cell.get();
+      region.cycles = region.cycles.wrapping_add(cycles);
+      region.calls += 1;
+      cell.set(region);
+    } else if len < N {
+      self.regions[len].set(Region { name, cycles, calls: 1 });
+      self.len.set(len + 1);
+    }
+  }
+
+  /// The total cycles and number of calls for `name` since the last reset.
+  #[inline]
+  pub fn get(&self, name: &str) -> Option<(u32, u32)> {
+    self.seen().find(|r| r.name == name).map(|r| (r.cycles, r.calls))
+  }
+
+  /// Writes one line per region, in the order they were first seen.
+  pub fn dump(&self, w: &mut impl fmt::Write) -> fmt::Result {
+    for r in self.seen() {
+      writeln!(w, "{}: {} cycles ({} calls)", r.name, r.cycles, r.calls)?;
+    }
+    Ok(())
+  }
+}
+impl<const N: usize> Default for RegionProfiler<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
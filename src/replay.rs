@@ -0,0 +1,201 @@
+//! Recording and replaying the keys, one [`KeyInput`] per frame.
+//!
+//! A [`Recorder`] wraps another [`KeySource`] and saves everything it reads
+//! into [`SRAM`]. A [`Replay`] is a `KeySource` that plays a recording back,
+//! either straight from SRAM or from a copy of the save file embedded in the
+//! ROM (eg: with `include_bytes!`). If the game only reads keys through a
+//! `KeySource`, a replay gives the exact same frames as the recording.
+//!
+//! ## Format
+//!
+//! The data starts with the 4 bytes `KREC`. After that is a list of runs,
+//! each 3 bytes: the pressed keys (as [`KeyInput::pressed`] bits, little
+//! endian), then the number of frames, `1..=255`. A run of 0 frames ends the
+//! data.
+//!
+//! ## Save Type
+//!
+//! Emulators and flash carts decide what save chip a game has by searching
+//! the ROM for a marker string. Using a [`Recorder`] or
+//! [`Replay::from_sram`] puts `SRAM_V113` into the ROM, so the game doesn't
+//! need to provide its own marker.
+
+use crate::{
+  keys::{HardwareKeys, KeySource},
+  KeyInput, SRAM,
+};
+
+const MAGIC: [u8; 4] = *b"KREC";
+
+/// The marker must start on a 4-byte boundary.
+#[repr(C, align(4))]
+struct SaveTypeMarker([u8; 12]);
+
+/// Tells emulators that the game uses 32K of SRAM.
+#[used]
+static SRAM_MARKER: SaveTypeMarker = SaveTypeMarker(*b"SRAM_V113\0\0\0");
+
+/// Makes sure the linker keeps [`SRAM_MARKER`] in the ROM.
+#[inline]
+fn keep_sram_marker() {
+  core::hint::black_box(&SRAM_MARKER);
+}
+
+/// Reads keys from another source, saving them into SRAM as it goes.
+///
+/// The recording is kept valid after every frame, so the game can be turned
+/// off (or crash) at any time. Once SRAM is full, keys are still passed along
+/// but no longer saved.
+pub struct Recorder<S: KeySource = HardwareKeys> {
+  source: S,
+  /// Offset of the current run.
+  pos: usize,
+  keys: KeyInput,
+  frames: u8,
+  full: bool,
+}
+impl<S: KeySource> Recorder<S> {
+  /// Starts a new recording, overwriting anything already in SRAM.
+  pub fn new(source: S) -> Self {
+    keep_sram_marker();
+    for (i, b) in MAGIC.iter().enumerate() {
+      SRAM.index(i).write(*b);
+    }
+    SRAM.index(MAGIC.len() + 2).write(0);
+    Self {
+      source,
+      pos: MAGIC.len(),
+      keys: KeyInput::NONE,
+      frames: 0,
+      full: false,
+    }
+  }
+
+  /// If SRAM has filled up.
+  #[inline]
+  pub const fn is_full(&self) -> bool {
+    self.full
+  }
+
+  /// Stops recording, giving back the inner source.
+  #[inline]
+  pub fn into_inner(self) -> S {
+    self.source
+  }
+
+  fn record(&mut self, keys: KeyInput) {
+    if self.full {
+      return;
+    }
+    if self.frames > 0 && keys == self.keys && self.frames < u8::MAX {
+      self.frames += 1;
+      SRAM.index(self.pos + 2).write(self.frames);
+      return;
+    }
+    let next = if self.frames > 0 { self.pos + 3 } else { self.pos };
+    // Room for this run and the end marker after it.
+    if next + 6 > SRAM.len() {
+      self.full = true;
+      return;
+    }
+    let [low, high] = keys.pressed().to_le_bytes();
+    SRAM.index(next).write(low);
+    SRAM.index(next + 1).write(high);
+    SRAM.index(next + 5).write(0);
+    SRAM.index(next + 2).write(1);
+    self.pos = next;
+    self.keys = keys;
+    self.frames = 1;
+  }
+}
+impl<S: KeySource> KeySource for Recorder<S> {
+  #[inline]
+  fn read_keys(&mut self) -> KeyInput {
+    let keys = self.source.read_keys();
+    self.record(keys);
+    keys
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Storage {
+  Sram,
+  Slice(&'static [u8]),
+}
+impl Storage {
+  #[inline]
+  fn get(self, i: usize) -> u8 {
+    match self {
+      Self::Sram => SRAM.get(i).map(|a| a.read()).unwrap_or(0),
+      Self::Slice(s) => s.get(i).copied().unwrap_or(0),
+    }
+  }
+}
+
+/// Plays back keys saved by a [`Recorder`].
+///
+/// After the recording ends, no keys are pressed.
+#[derive(Clone)]
+pub struct Replay {
+  storage: Storage,
+  /// Offset of the next run.
+  pos: usize,
+  keys: KeyInput,
+  frames_left: u8,
+  finished: bool,
+}
+impl Replay {
+  fn new(storage: Storage) -> Option<Self> {
+    if (0..MAGIC.len()).any(|i| storage.get(i) != MAGIC[i]) {
+      return None;
+    }
+    Some(Self {
+      storage,
+      pos: MAGIC.len(),
+      keys: KeyInput::NONE,
+      frames_left: 0,
+      finished: false,
+    })
+  }
+
+  /// Plays the recording in SRAM, or `None` if SRAM has no recording.
+  #[inline]
+  pub fn from_sram() -> Option<Self> {
+    keep_sram_marker();
+    Self::new(Storage::Sram)
+  }
+
+  /// Plays a recording stored in memory (such as a save file included into
+  /// the ROM), or `None` if the data isn't a recording.
+  #[inline]
+  pub fn from_slice(data: &'static [u8]) -> Option<Self> {
+    Self::new(Storage::Slice(data))
+  }
+
+  /// If every frame of the recording has been played.
+  #[inline]
+  pub const fn is_finished(&self) -> bool {
+    self.finished
+  }
+}
+impl KeySource for Replay {
+  fn read_keys(&mut self) -> KeyInput {
+    if self.frames_left == 0 && !self.finished {
+      let low = self.storage.get(self.pos);
+      let high = self.storage.get(self.pos + 1);
+      let frames = self.storage.get(self.pos + 2);
+      if frames == 0 {
+        self.finished = true;
+      } else {
+        self.keys = KeyInput::from_pressed(u16::from_le_bytes([low, high]));
+        self.frames_left = frames;
+        self.pos += 3;
+      }
+    }
+    if self.finished {
+      return KeyInput::NONE;
+    }
+    self.frames_left -= 1;
+    self.keys
+  }
+}
@@ -0,0 +1,865 @@
+//! Sound.
+//!
+//! The GBA has two "Direct Sound" channels, A and B, which play signed 8-bit
+//! samples. Each has a 32 byte FIFO that's refilled by DMA1 or DMA2 (with the
+//! "special" start timing), and timer 0 or 1 sets how fast samples are taken
+//! from the FIFO.
+//!
+//! The [`Mixer`] uses channel A as the left speaker and channel B as the right
+//! speaker, mixing any number of samples in software.
+//!
+//! There are also the four "PSG" channels from the Game Boy: two square waves
+//! (channel 1 with a frequency sweep), a wave channel that plays 4-bit samples
+//! from wave RAM, and a noise channel. [`play_square`], [`play_noise`],
+//! [`load_wave`] and [`play_wave`] cover the simple uses.
+
+use bitfrob::{u16_get_bit, u16_get_value, u16_with_bit, u16_with_value};
+use voladdress::{Safe, VolAddress, VolBlock};
+
+use crate::{
+  dma::{
+    DestAddrControl, DmaControl, DmaStartTiming, DMA1_CONTROL, DMA1_DEST,
+    DMA1_SRC, DMA2_CONTROL, DMA2_DEST, DMA2_SRC,
+  },
+  timer::{Prescaler, Timer, TimerControl},
+};
+
+/// How loud a Direct Sound channel is.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum DirectSoundVolume {
+  #[default]
+  Half = 0,
+  Full = 1,
+}
+
+/// Mixing and routing of the Direct Sound channels.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct DirectSoundControl(u16);
+impl DirectSoundControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+
+  /// Volume of the PSG channels: 0 is 25%, 1 is 50%, 2 is 100%.
+  #[inline]
+  pub const fn with_psg_volume(self, volume: u16) -> Self {
+    Self(u16_with_value(0, 1, self.0, volume))
+  }
+  #[inline]
+  pub const fn psg_volume(self) -> u16 {
+    u16_get_value(0, 1, self.0)
+  }
+
+  #[inline]
+  pub const fn with_a_volume(self, volume: DirectSoundVolume) -> Self {
+    Self(u16_with_bit(2, self.0, volume as u16 != 0))
+  }
+  #[inline]
+  pub const fn a_volume(self) -> DirectSoundVolume {
+    if u16_get_bit(2, self.0) {
+      DirectSoundVolume::Full
+    } else {
+      DirectSoundVolume::Half
+    }
+  }
+  #[inline]
+  pub const fn with_b_volume(self, volume: DirectSoundVolume) -> Self {
+    Self(u16_with_bit(3, self.0, volume as u16 != 0))
+  }
+  #[inline]
+  pub const fn b_volume(self) -> DirectSoundVolume {
+    if u16_get_bit(3, self.0) {
+      DirectSoundVolume::Full
+    } else {
+      DirectSoundVolume::Half
+    }
+  }
+
+  #[inline]
+  pub const fn with_a_right(self, right: bool) -> Self {
+    Self(u16_with_bit(8, self.0, right))
+  }
+  #[inline]
+  pub const fn a_right(self) -> bool {
+    u16_get_bit(8, self.0)
+  }
+  #[inline]
+  pub const fn with_a_left(self, left: bool) -> Self {
+    Self(u16_with_bit(9, self.0, left))
+  }
+  #[inline]
+  pub const fn a_left(self) -> bool {
+    u16_get_bit(9, self.0)
+  }
+  /// If channel A uses timer 1 (otherwise timer 0).
+  #[inline]
+  pub const fn with_a_timer1(self, timer1: bool) -> Self {
+    Self(u16_with_bit(10, self.0, timer1))
+  }
+  #[inline]
+  pub const fn a_timer1(self) -> bool {
+    u16_get_bit(10, self.0)
+  }
+  /// Empties FIFO A when written. Always reads as `false`.
+  #[inline]
+  pub const fn with_a_reset(self, reset: bool) -> Self {
+    Self(u16_with_bit(11, self.0, reset))
+  }
+
+  #[inline]
+  pub const fn with_b_right(self, right: bool) -> Self {
+    Self(u16_with_bit(12, self.0, right))
+  }
+  #[inline]
+  pub const fn b_right(self) -> bool {
+    u16_get_bit(12, self.0)
+  }
+  #[inline]
+  pub const fn with_b_left(self, left: bool) -> Self {
+    Self(u16_with_bit(13, self.0, left))
+  }
+  #[inline]
+  pub const fn b_left(self) -> bool {
+    u16_get_bit(13, self.0)
+  }
+  /// If channel B uses timer 1 (otherwise timer 0).
+  #[inline]
+  pub const fn with_b_timer1(self, timer1: bool) -> Self {
+    Self(u16_with_bit(14, self.0, timer1))
+  }
+  #[inline]
+  pub const fn b_timer1(self) -> bool {
+    u16_get_bit(14, self.0)
+  }
+  /// Empties FIFO B when written. Always reads as `false`.
+  #[inline]
+  pub const fn with_b_reset(self, reset: bool) -> Self {
+    Self(u16_with_bit(15, self.0, reset))
+  }
+}
+
+/// The master sound enable, and which PSG channels are playing.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct SoundStatus(u16);
+#[rustfmt::skip]
+impl SoundStatus {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  #[inline]
+  pub const fn sound1_playing(self) -> bool { u16_get_bit(0, self.0) }
+  #[inline]
+  pub const fn sound2_playing(self) -> bool { u16_get_bit(1, self.0) }
+  #[inline]
+  pub const fn sound3_playing(self) -> bool { u16_get_bit(2, self.0) }
+  #[inline]
+  pub const fn sound4_playing(self) -> bool { u16_get_bit(3, self.0) }
+  /// Turns on all sound. While this is off, the other sound registers can't
+  /// be written.
+  #[inline]
+  pub const fn with_enabled(self, b: bool) -> Self { Self(u16_with_bit(7, self.0, b)) }
+  #[inline]
+  pub const fn enabled(self) -> bool { u16_get_bit(7, self.0) }
+}
+
+pub const SOUNDCNT_H: VolAddress<DirectSoundControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0082) };
+pub const SOUNDCNT_X: VolAddress<SoundStatus, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0084) };
+
+/// Write 4 samples at a time into Direct Sound channel A.
+pub const FIFO_A: VolAddress<u32, (), Safe> =
+  unsafe { VolAddress::new(0x0400_00A0) };
+/// Write 4 samples at a time into Direct Sound channel B.
+pub const FIFO_B: VolAddress<u32, (), Safe> =
+  unsafe { VolAddress::new(0x0400_00A4) };
+
+/// A sample rate that's an exact number of samples per frame.
+///
+/// | Rate     | Samples per frame | CPU cycles per sample |
+/// |:--------:|:-----------------:|:---------------------:|
+/// | 5734 Hz  | 96                | 2926                  |
+/// | 10512 Hz | 176               | 1596                  |
+/// | 13379 Hz | 224               | 1254                  |
+/// | 18157 Hz | 304               | 924                   |
+/// | 21024 Hz | 352               | 798                   |
+/// | 26758 Hz | 448               | 627                   |
+/// | 31536 Hz | 528               | 532                   |
+/// | 36314 Hz | 608               | 462                   |
+/// | 40137 Hz | 672               | 418                   |
+/// | 42048 Hz | 704               | 399                   |
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixRate {
+  Hz5734,
+  Hz10512,
+  Hz13379,
+  #[default]
+  Hz18157,
+  Hz21024,
+  Hz26758,
+  Hz31536,
+  Hz36314,
+  Hz40137,
+  Hz42048,
+}
+impl MixRate {
+  #[inline]
+  pub const fn samples_per_frame(self) -> usize {
+    match self {
+      Self::Hz5734 => 96,
+      Self::Hz10512 => 176,
+      Self::Hz13379 => 224,
+      Self::Hz18157 => 304,
+      Self::Hz21024 => 352,
+      Self::Hz26758 => 448,
+      Self::Hz31536 => 528,
+      Self::Hz36314 => 608,
+      Self::Hz40137 => 672,
+      Self::Hz42048 => 704,
+    }
+  }
+  #[inline]
+  pub const fn cycles_per_sample(self) -> u16 {
+    match self {
+      Self::Hz5734 => 2926,
+      Self::Hz10512 => 1596,
+      Self::Hz13379 => 1254,
+      Self::Hz18157 => 924,
+      Self::Hz21024 => 798,
+      Self::Hz26758 => 627,
+      Self::Hz31536 => 532,
+      Self::Hz36314 => 462,
+      Self::Hz40137 => 418,
+      Self::Hz42048 => 399,
+    }
+  }
+}
+
+/// The most samples per frame of any [`MixRate`].
+pub const MAX_SAMPLES_PER_FRAME: usize = 704;
+
+/// One sound playing in a [`Mixer`].
+#[derive(Clone, Copy)]
+pub struct Channel {
+  data: &'static [i8],
+  /// 20.12 fixed point.
+  pos: u32,
+  step: u32,
+  volume: u8,
+  pan: i8,
+  looping: bool,
+}
+impl Channel {
+  /// Plays `data` once, at full volume, centered, one input sample per output
+  /// sample.
+  ///
+  /// ## Panics
+  /// * If `data` is 1 MiB or longer, since the position wouldn't fit in 20.12
+  ///   fixed point.
+  #[inline]
+  pub const fn new(data: &'static [i8]) -> Self {
+    assert!(data.len() < 1 << 20, "sample data is too long");
+    Self { data, pos: 0, step: 0x1000, volume: 64, pan: 0, looping: false }
+  }
+  /// Volume, `0..=64`.
+  #[inline]
+  pub const fn with_volume(self, volume: u8) -> Self {
+    let volume = if volume > 64 { 64 } else { volume };
+    Self { volume, ..self }
+  }
+  /// Pan, from `-64` (left only) to `64` (right only).
+  #[inline]
+  pub const fn with_pan(self, pan: i8) -> Self {
+    let pan = if pan < -64 {
+      -64
+    } else if pan > 64 {
+      64
+    } else {
+      pan
+    };
+    Self { pan, ..self }
+  }
+  /// Input samples per output sample, as 20.12 fixed point (`0x1000` is 1.0).
+  ///
+  /// Eg: to play an 8000 Hz sample with an 18157 Hz mixer, use
+  /// `0x1000 * 8000 / 18157`.
+  #[inline]
+  pub const fn with_step(self, step: u32) -> Self {
+    Self { step, ..self }
+  }
+  /// Starts again from the beginning when the end is reached.
+  #[inline]
+  pub const fn with_looping(self, looping: bool) -> Self {
+    Self { looping, ..self }
+  }
+
+  /// Adds this channel into the mix, returning `false` once it's done.
+  fn mix_into(&mut self, left: &mut [i16], right: &mut [i16]) -> bool {
+    let volume = self.volume as i16;
+    let pan = self.pan as i16;
+    let left_volume = (volume * (64 - pan.max(0))) >> 6;
+    let right_volume = (volume * (64 + pan.min(0))) >> 6;
+    let end = (self.data.len() as u32) << 12;
+    if end == 0 {
+      return false;
+    }
+    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+      let sample = self.data[(self.pos >> 12) as usize] as i16;
+      *l = l.saturating_add(sample * left_volume);
+      *r = r.saturating_add(sample * right_volume);
+      // `pos` is always less than `end`, so this can't overflow.
+      let remaining = end - self.pos;
+      if self.step < remaining {
+        self.pos += self.step;
+      } else if self.looping {
+        self.pos = (self.step - remaining) % end;
+      } else {
+        return false;
+      }
+    }
+    true
+  }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C, align(4))]
+struct SampleBuffer([i8; MAX_SAMPLES_PER_FRAME]);
+
+/// Mixes up to `N` channels into the Direct Sound FIFOs.
+///
+/// Each frame's output is mixed one frame ahead into a second buffer, and
+/// [`vblank`](Self::vblank) swaps the buffers. Since DMA reads the buffers
+/// directly, keep the mixer in one place (eg: a `static`) while it's running.
+///
+/// Uses timer 0, DMA1 and DMA2.
+pub struct Mixer<const N: usize> {
+  channels: [Option<Channel>; N],
+  rate: MixRate,
+  left: [SampleBuffer; 2],
+  right: [SampleBuffer; 2],
+  current: usize,
+}
+impl<const N: usize> Mixer<N> {
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      channels: [None; N],
+      rate: MixRate::Hz18157,
+      left: [SampleBuffer([0; MAX_SAMPLES_PER_FRAME]); 2],
+      right: [SampleBuffer([0; MAX_SAMPLES_PER_FRAME]); 2],
+      current: 0,
+    }
+  }
+
+  /// Sets up the sound hardware and timer 0 for mixing at `rate`.
+  ///
+  /// Output starts on the next call to [`vblank`](Self::vblank).
+  pub fn start(&mut self, rate: MixRate) {
+    self.rate = rate;
+    self.left = [SampleBuffer([0; MAX_SAMPLES_PER_FRAME]); 2];
+    self.right = [SampleBuffer([0; MAX_SAMPLES_PER_FRAME]); 2];
+    SOUNDCNT_X.write(SoundStatus::new().with_enabled(true));
+    SOUNDCNT_H.write(
+      DirectSoundControl::new()
+        .with_psg_volume(2)
+        .with_a_volume(DirectSoundVolume::Full)
+        .with_a_left(true)
+        .with_a_reset(true)
+        .with_b_volume(DirectSoundVolume::Full)
+        .with_b_right(true)
+        .with_b_reset(true),
+    );
+    Timer::TIMER0.set_reload(0u16.wrapping_sub(rate.cycles_per_sample()));
+    Timer::TIMER0.start(TimerControl::new().with_prescaler(Prescaler::Div1));
+  }
+
+  /// Stops DMA1, DMA2, and timer 0.
+  pub fn stop(&mut self) {
+    unsafe {
+      DMA1_CONTROL.write(DmaControl::new());
+      DMA2_CONTROL.write(DmaControl::new());
+    }
+    Timer::TIMER0.stop();
+  }
+
+  /// Plays `channel` in slot `index`, replacing anything already there.
+  #[inline]
+  pub fn play(&mut self, index: usize, channel: Channel) {
+    self.channels[index] = Some(channel);
+  }
+  /// Stops whatever is playing in slot `index`.
+  #[inline]
+  pub fn stop_channel(&mut self, index: usize) {
+    self.channels[index] = None;
+  }
+  /// The channel in slot `index`, if it's still playing.
+  #[inline]
+  pub fn channel_mut(&mut self, index: usize) -> Option<&mut Channel> {
+    self.channels[index].as_mut()
+  }
+  #[inline]
+  pub fn is_playing(&self, index: usize) -> bool {
+    self.channels[index].is_some()
+  }
+  #[inline]
+  pub fn set_volume(&mut self, index: usize, volume: u8) {
+    if let Some(c) = self.channel_mut(index) {
+      *c = c.with_volume(volume);
+    }
+  }
+  #[inline]
+  pub fn set_pan(&mut self, index: usize, pan: i8) {
+    if let Some(c) = self.channel_mut(index) {
+      *c = c.with_pan(pan);
+    }
+  }
+
+  /// Call this once per frame, at the start of VBlank.
+  ///
+  /// Starts playing the frame mixed last time, then mixes the next frame.
+  pub fn vblank(&mut self) {
+    self.current ^= 1;
+    let control = DmaControl::new()
+      .with_dest_addr(DestAddrControl::Fixed)
+      .with_repeat(true)
+      .with_32bit(true)
+      .with_start_timing(DmaStartTiming::Special)
+      .with_enabled(true);
+    unsafe {
+      DMA1_CONTROL.write(DmaControl::new());
+      DMA1_SRC.write(self.left[self.current].0.as_ptr().cast());
+      DMA1_DEST.write(FIFO_A.as_usize() as *mut _);
+      DMA1_CONTROL.write(control);
+      DMA2_CONTROL.write(DmaControl::new());
+      DMA2_SRC.write(self.right[self.current].0.as_ptr().cast());
+      DMA2_DEST.write(FIFO_B.as_usize() as *mut _);
+      DMA2_CONTROL.write(control);
+    }
+    self.mix(self.current ^ 1);
+  }
+
+  fn mix(&mut self, buffer: usize) {
+    let len = self.rate.samples_per_frame();
+    let mut left = [0_i16; MAX_SAMPLES_PER_FRAME];
+    let mut right = [0_i16; MAX_SAMPLES_PER_FRAME];
+    for slot in self.channels.iter_mut() {
+      if let Some(channel) = slot {
+        if !channel.mix_into(&mut left[..len], &mut right[..len]) {
+          *slot = None;
+        }
+      }
+    }
+    let out_left = &mut self.left[buffer].0[..len];
+    let out_right = &mut self.right[buffer].0[..len];
+    for (out, acc) in out_left.iter_mut().zip(left.iter()) {
+      *out = (acc >> 6).clamp(-128, 127) as i8;
+    }
+    for (out, acc) in out_right.iter_mut().zip(right.iter()) {
+      *out = (acc >> 6).clamp(-128, 127) as i8;
+    }
+  }
+}
+impl<const N: usize> Default for Mixer<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// The frequency sweep of PSG channel 1.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct SweepControl(u16);
+#[rustfmt::skip]
+impl SweepControl {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// Each sweep step changes the rate by `rate >> shift`, `0..=7`.
+  #[inline]
+  pub const fn with_shift(self, shift: u16) -> Self { Self(u16_with_value(0, 2, self.0, shift)) }
+  #[inline]
+  pub const fn shift(self) -> u16 { u16_get_value(0, 2, self.0) }
+  /// The frequency goes down (otherwise up).
+  #[inline]
+  pub const fn with_decrease(self, b: bool) -> Self { Self(u16_with_bit(3, self.0, b)) }
+  #[inline]
+  pub const fn decrease(self) -> bool { u16_get_bit(3, self.0) }
+  /// Time between sweep steps, in 128ths of a second, `0..=7`. 0 is off.
+  #[inline]
+  pub const fn with_time(self, time: u16) -> Self { Self(u16_with_value(4, 6, self.0, time)) }
+  #[inline]
+  pub const fn time(self) -> u16 { u16_get_value(4, 6, self.0) }
+}
+
+/// The fraction of each wave cycle that a square wave is high.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum SquareDuty {
+  Eighth = 0,
+  Quarter = 1,
+  #[default]
+  Half = 2,
+  ThreeQuarters = 3,
+}
+
+/// A volume envelope for PSG channels 1, 2, and 4.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Envelope {
+  /// Starting volume, `0..=15`.
+  pub volume: u16,
+  /// The volume goes up (otherwise down).
+  pub increase: bool,
+  /// Time between volume steps, in 64ths of a second, `0..=7`. 0 holds the
+  /// volume steady.
+  pub step_time: u16,
+}
+
+/// Length, duty, and envelope of PSG channels 1, 2 (and 4, without duty).
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct DutyLenEnvelope(u16);
+impl DutyLenEnvelope {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// Sound length is `(64 - length) / 256` seconds, if length is enabled.
+  ///
+  /// Write only.
+  #[inline]
+  pub const fn with_length(self, length: u16) -> Self {
+    Self(u16_with_value(0, 5, self.0, length))
+  }
+  #[inline]
+  pub const fn with_duty(self, duty: SquareDuty) -> Self {
+    Self(u16_with_value(6, 7, self.0, duty as u16))
+  }
+  #[inline]
+  pub const fn duty(self) -> SquareDuty {
+    match u16_get_value(6, 7, self.0) {
+      0 => SquareDuty::Eighth,
+      1 => SquareDuty::Quarter,
+      2 => SquareDuty::Half,
+      _ => SquareDuty::ThreeQuarters,
+    }
+  }
+  #[inline]
+  pub const fn with_envelope(self, envelope: Envelope) -> Self {
+    let x = u16_with_value(8, 10, self.0, envelope.step_time);
+    let x = u16_with_bit(11, x, envelope.increase);
+    Self(u16_with_value(12, 15, x, envelope.volume))
+  }
+  #[inline]
+  pub const fn envelope(self) -> Envelope {
+    Envelope {
+      volume: u16_get_value(12, 15, self.0),
+      increase: u16_get_bit(11, self.0),
+      step_time: u16_get_value(8, 10, self.0),
+    }
+  }
+}
+
+/// Frequency and (re)start of PSG channels 1, 2, and 3.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct FrequencyControl(u16);
+#[rustfmt::skip]
+impl FrequencyControl {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// The frequency is `131072 / (2048 - rate)` Hz for channels 1 and 2, or
+  /// `2097152 / (2048 - rate)` samples per second for channel 3.
+  ///
+  /// Write only.
+  #[inline]
+  pub const fn with_rate(self, rate: u16) -> Self { Self(u16_with_value(0, 10, self.0, rate)) }
+  /// Stops the sound once its length runs out (otherwise plays forever).
+  #[inline]
+  pub const fn with_length_enabled(self, b: bool) -> Self { Self(u16_with_bit(14, self.0, b)) }
+  #[inline]
+  pub const fn length_enabled(self) -> bool { u16_get_bit(14, self.0) }
+  /// Starts the sound from the beginning. Write only.
+  #[inline]
+  pub const fn with_restart(self, b: bool) -> Self { Self(u16_with_bit(15, self.0, b)) }
+}
+
+/// Wave RAM setup of PSG channel 3.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct WaveControl(u16);
+#[rustfmt::skip]
+impl WaveControl {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// Plays both banks as one 64 sample wave (otherwise one 32 sample bank).
+  #[inline]
+  pub const fn with_two_banks(self, b: bool) -> Self { Self(u16_with_bit(5, self.0, b)) }
+  #[inline]
+  pub const fn two_banks(self) -> bool { u16_get_bit(5, self.0) }
+  /// Plays bank 1 (otherwise bank 0). The CPU accesses the *other* bank.
+  #[inline]
+  pub const fn with_bank1(self, b: bool) -> Self { Self(u16_with_bit(6, self.0, b)) }
+  #[inline]
+  pub const fn bank1(self) -> bool { u16_get_bit(6, self.0) }
+  #[inline]
+  pub const fn with_enabled(self, b: bool) -> Self { Self(u16_with_bit(7, self.0, b)) }
+  #[inline]
+  pub const fn enabled(self) -> bool { u16_get_bit(7, self.0) }
+}
+
+/// The output level of PSG channel 3.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum WaveVolume {
+  #[default]
+  Mute = 0,
+  Full = 1,
+  Half = 2,
+  Quarter = 3,
+}
+
+/// Length and volume of PSG channel 3.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct WaveLenVolume(u16);
+impl WaveLenVolume {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+  /// Sound length is `(256 - length) / 256` seconds, if length is enabled.
+  ///
+  /// Write only.
+  #[inline]
+  pub const fn with_length(self, length: u16) -> Self {
+    Self(u16_with_value(0, 7, self.0, length))
+  }
+  #[inline]
+  pub const fn with_volume(self, volume: WaveVolume) -> Self {
+    Self(u16_with_value(13, 14, self.0, volume as u16))
+  }
+  #[inline]
+  pub const fn volume(self) -> WaveVolume {
+    match u16_get_value(13, 14, self.0) {
+      0 => WaveVolume::Mute,
+      1 => WaveVolume::Full,
+      2 => WaveVolume::Half,
+      _ => WaveVolume::Quarter,
+    }
+  }
+  /// Plays at 75% volume, overriding the normal volume.
+  #[inline]
+  pub const fn with_force_75(self, force: bool) -> Self {
+    Self(u16_with_bit(15, self.0, force))
+  }
+  #[inline]
+  pub const fn force_75(self) -> bool {
+    u16_get_bit(15, self.0)
+  }
+}
+
+/// Frequency and (re)start of PSG channel 4.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct NoiseControl(u16);
+#[rustfmt::skip]
+impl NoiseControl {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// The noise frequency is `524288 / r / 2^(shift + 1)` Hz, where `r` is the
+  /// divider (or 0.5 when the divider is 0). The divider is `0..=7`.
+  #[inline]
+  pub const fn with_divider(self, divider: u16) -> Self { Self(u16_with_value(0, 2, self.0, divider)) }
+  #[inline]
+  pub const fn divider(self) -> u16 { u16_get_value(0, 2, self.0) }
+  /// Uses a 7-bit noise counter (otherwise 15-bit), which sounds more tonal.
+  #[inline]
+  pub const fn with_short(self, b: bool) -> Self { Self(u16_with_bit(3, self.0, b)) }
+  #[inline]
+  pub const fn short(self) -> bool { u16_get_bit(3, self.0) }
+  /// `0..=13`, higher values are lower pitched.
+  #[inline]
+  pub const fn with_shift(self, shift: u16) -> Self { Self(u16_with_value(4, 7, self.0, shift)) }
+  #[inline]
+  pub const fn shift(self) -> u16 { u16_get_value(4, 7, self.0) }
+  /// Stops the sound once its length runs out (otherwise plays forever).
+  #[inline]
+  pub const fn with_length_enabled(self, b: bool) -> Self { Self(u16_with_bit(14, self.0, b)) }
+  #[inline]
+  pub const fn length_enabled(self) -> bool { u16_get_bit(14, self.0) }
+  /// Starts the sound from the beginning. Write only.
+  #[inline]
+  pub const fn with_restart(self, b: bool) -> Self { Self(u16_with_bit(15, self.0, b)) }
+}
+
+/// Master volume and speaker routing of the PSG channels.
+///
+/// The per-channel methods panic if the channel isn't `1..=4`.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct PsgMix(u16);
+#[rustfmt::skip]
+impl PsgMix {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// `0..=7`
+  #[inline]
+  pub const fn with_right_volume(self, volume: u16) -> Self { Self(u16_with_value(0, 2, self.0, volume)) }
+  #[inline]
+  pub const fn right_volume(self) -> u16 { u16_get_value(0, 2, self.0) }
+  /// `0..=7`
+  #[inline]
+  pub const fn with_left_volume(self, volume: u16) -> Self { Self(u16_with_value(4, 6, self.0, volume)) }
+  #[inline]
+  pub const fn left_volume(self) -> u16 { u16_get_value(4, 6, self.0) }
+  /// Plays channel `n`, `1..=4`, on the right speaker.
+  #[inline]
+  pub const fn with_right(self, n: u32, b: bool) -> Self { Self(u16_with_bit(psg_bit(8, n), self.0, b)) }
+  #[inline]
+  pub const fn right(self, n: u32) -> bool { u16_get_bit(psg_bit(8, n), self.0) }
+  /// Plays channel `n`, `1..=4`, on the left speaker.
+  #[inline]
+  pub const fn with_left(self, n: u32, b: bool) -> Self { Self(u16_with_bit(psg_bit(12, n), self.0, b)) }
+  #[inline]
+  pub const fn left(self, n: u32) -> bool { u16_get_bit(psg_bit(12, n), self.0) }
+}
+
+/// The bit for PSG channel `n` in a group of 4 bits starting at `base`.
+///
+/// ## Panics
+/// * If `n` isn't `1..=4`.
+#[inline]
+const fn psg_bit(base: u32, n: u32) -> u32 {
+  assert!(matches!(n, 1..=4), "PSG channels are 1..=4");
+  base + n - 1
+}
+
+/// The output bias level and PWM resolution.
+///
+/// The BIOS sets this at boot, and it usually shouldn't be changed.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct SoundBias(u16);
+#[rustfmt::skip]
+impl SoundBias {
+  #[inline]
+  pub const fn new() -> Self { Self(0) }
+  /// `0..=0x1FF`, normally `0x100`.
+  #[inline]
+  pub const fn with_level(self, level: u16) -> Self { Self(u16_with_value(1, 9, self.0, level)) }
+  #[inline]
+  pub const fn level(self) -> u16 { u16_get_value(1, 9, self.0) }
+  /// 0 is 9-bit at 32768 Hz, up to 3 for 6-bit at 262144 Hz.
+  #[inline]
+  pub const fn with_resolution(self, resolution: u16) -> Self { Self(u16_with_value(14, 15, self.0, resolution)) }
+  #[inline]
+  pub const fn resolution(self) -> u16 { u16_get_value(14, 15, self.0) }
+}
+
+pub const SOUND1CNT_L: VolAddress<SweepControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0060) };
+pub const SOUND1CNT_H: VolAddress<DutyLenEnvelope, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0062) };
+pub const SOUND1CNT_X: VolAddress<FrequencyControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0064) };
+
+pub const SOUND2CNT_L: VolAddress<DutyLenEnvelope, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0068) };
+pub const SOUND2CNT_H: VolAddress<FrequencyControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_006C) };
+
+pub const SOUND3CNT_L: VolAddress<WaveControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0070) };
+pub const SOUND3CNT_H: VolAddress<WaveLenVolume, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0072) };
+pub const SOUND3CNT_X: VolAddress<FrequencyControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0074) };
+
+/// Channel 4 has no duty, so those bits are ignored.
+pub const SOUND4CNT_L: VolAddress<DutyLenEnvelope, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0078) };
+pub const SOUND4CNT_H: VolAddress<NoiseControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_007C) };
+
+pub const SOUNDCNT_L: VolAddress<PsgMix, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0080) };
+pub const SOUNDBIAS: VolAddress<SoundBias, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0088) };
+
+/// The bank of wave RAM that isn't selected for playback in `SOUND3CNT_L`.
+///
+/// Each byte is two 4-bit samples, with the high nibble played first.
+pub const WAVE_RAM: VolBlock<u32, Safe, Safe, 4> =
+  unsafe { VolBlock::new(0x0400_0090) };
+
+/// Turns on sound (if it's off) and plays PSG channel `n` on both speakers.
+///
+/// The PSG volumes in `SOUNDCNT_L` and `SOUNDCNT_H` are set to full every time,
+/// since something else (such as [`Mixer::start`]) may have changed them.
+fn enable_psg_channel(n: u32) {
+  if !SOUNDCNT_X.read().enabled() {
+    SOUNDCNT_X.write(SoundStatus::new().with_enabled(true));
+  }
+  SOUNDCNT_L.write(
+    SOUNDCNT_L
+      .read()
+      .with_left_volume(7)
+      .with_right_volume(7)
+      .with_left(n, true)
+      .with_right(n, true),
+  );
+  SOUNDCNT_H.write(SOUNDCNT_H.read().with_psg_volume(2));
+}
+
+/// Plays a square wave of `freq` Hz (`64..=131072`) on PSG channel 2.
+///
+/// The sound plays until the envelope fades it out, or forever if it doesn't.
+pub fn play_square(freq: u32, duty: SquareDuty, envelope: Envelope) {
+  enable_psg_channel(2);
+  let rate = 2048 - 131072 / freq.clamp(64, 131072);
+  SOUND2CNT_L
+    .write(DutyLenEnvelope::new().with_duty(duty).with_envelope(envelope));
+  SOUND2CNT_H
+    .write(FrequencyControl::new().with_rate(rate as u16).with_restart(true));
+}
+
+/// Plays noise on PSG channel 4. See [`NoiseControl`] for the `divider` and
+/// `shift` values.
+///
+/// The sound plays until the envelope fades it out, or forever if it doesn't.
+pub fn play_noise(divider: u16, shift: u16, short: bool, envelope: Envelope) {
+  enable_psg_channel(4);
+  SOUND4CNT_L.write(DutyLenEnvelope::new().with_envelope(envelope));
+  SOUND4CNT_H.write(
+    NoiseControl::new()
+      .with_divider(divider)
+      .with_shift(shift)
+      .with_short(short)
+      .with_restart(true),
+  );
+}
+
+/// Loads a 32 sample wave (4-bit samples, high nibble first) into bank 0 of
+/// wave RAM, and sets PSG channel 3 to play bank 0.
+pub fn load_wave(wave: &[u32; 4]) {
+  enable_psg_channel(3);
+  // Play bank 1 so that the CPU can write bank 0.
+  SOUND3CNT_L.write(WaveControl::new().with_bank1(true));
+  for (addr, w) in WAVE_RAM.iter().zip(wave.iter()) {
+    addr.write(*w);
+  }
+  SOUND3CNT_L.write(WaveControl::new().with_enabled(true));
+}
+
+/// Plays the loaded wave as a tone of `freq` Hz (`32..=65536`) on PSG
+/// channel 3.
+pub fn play_wave(freq: u32, volume: WaveVolume) {
+  enable_psg_channel(3);
+  let rate = 2048 - 65536 / freq.clamp(32, 65536);
+  SOUND3CNT_H.write(WaveLenVolume::new().with_volume(volume));
+  SOUND3CNT_X
+    .write(FrequencyControl::new().with_rate(rate as u16).with_restart(true));
+}
@@ -0,0 +1,180 @@
+//! The four hardware timers.
+//!
+//! Each timer is a 16-bit counter that goes up once per "tick", where a tick is
+//! some number of CPU cycles set by the [`Prescaler`], or (with cascade) one
+//! overflow of the previous timer. When a timer overflows it restarts from its
+//! reload value, and can send an interrupt.
+//!
+//! The reload and counter share an address: writes set the reload value,
+//! reads give the current counter. The reload value is copied into the counter
+//! when the timer goes from disabled to enabled.
+//!
+//! Timers 0 and 1 also set the sample rate of the Direct Sound FIFOs.
+
+use bitfrob::{u16_get_bit, u16_get_value, u16_with_bit, u16_with_value};
+use voladdress::{Safe, VolAddress};
+
+/// How many CPU cycles make up one timer tick.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u16)]
+pub enum Prescaler {
+  /// 16.78 MHz
+  #[default]
+  Div1 = 0,
+  /// 262.2 kHz
+  Div64 = 1,
+  /// 65.54 kHz
+  Div256 = 2,
+  /// 16.38 kHz
+  Div1024 = 3,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(transparent)]
+pub struct TimerControl(u16);
+impl TimerControl {
+  #[inline]
+  pub const fn new() -> Self {
+    Self(0)
+  }
+
+  #[inline]
+  pub const fn with_prescaler(self, prescaler: Prescaler) -> Self {
+    Self(u16_with_value(0, 1, self.0, prescaler as u16))
+  }
+  #[inline]
+  pub const fn prescaler(self) -> Prescaler {
+    match u16_get_value(0, 1, self.0) {
+      0 => Prescaler::Div1,
+      1 => Prescaler::Div64,
+      2 => Prescaler::Div256,
+      _ => Prescaler::Div1024,
+    }
+  }
+
+  /// Ticks once each time the previous timer overflows, ignoring the
+  /// prescaler. Has no effect on timer 0.
+  #[inline]
+  pub const fn with_cascade(self, cascade: bool) -> Self {
+    Self(u16_with_bit(2, self.0, cascade))
+  }
+  #[inline]
+  pub const fn cascade(self) -> bool {
+    u16_get_bit(2, self.0)
+  }
+
+  /// Sends an interrupt when the counter overflows.
+  #[inline]
+  pub const fn with_irq(self, irq: bool) -> Self {
+    Self(u16_with_bit(6, self.0, irq))
+  }
+  #[inline]
+  pub const fn irq(self) -> bool {
+    u16_get_bit(6, self.0)
+  }
+
+  #[inline]
+  pub const fn with_enabled(self, enabled: bool) -> Self {
+    Self(u16_with_bit(7, self.0, enabled))
+  }
+  #[inline]
+  pub const fn enabled(self) -> bool {
+    u16_get_bit(7, self.0)
+  }
+}
+
+pub const TIMER0_COUNT: VolAddress<u16, Safe, ()> =
+  unsafe { VolAddress::new(0x0400_0100) };
+pub const TIMER0_RELOAD: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0100) };
+pub const TIMER0_CONTROL: VolAddress<TimerControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0102) };
+
+pub const TIMER1_COUNT: VolAddress<u16, Safe, ()> =
+  unsafe { VolAddress::new(0x0400_0104) };
+pub const TIMER1_RELOAD: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0104) };
+pub const TIMER1_CONTROL: VolAddress<TimerControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_0106) };
+
+pub const TIMER2_COUNT: VolAddress<u16, Safe, ()> =
+  unsafe { VolAddress::new(0x0400_0108) };
+pub const TIMER2_RELOAD: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_0108) };
+pub const TIMER2_CONTROL: VolAddress<TimerControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_010A) };
+
+pub const TIMER3_COUNT: VolAddress<u16, Safe, ()> =
+  unsafe { VolAddress::new(0x0400_010C) };
+pub const TIMER3_RELOAD: VolAddress<u16, (), Safe> =
+  unsafe { VolAddress::new(0x0400_010C) };
+pub const TIMER3_CONTROL: VolAddress<TimerControl, Safe, Safe> =
+  unsafe { VolAddress::new(0x0400_010E) };
+
+/// A handle to one of the four timers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Timer(usize);
+impl Timer {
+  pub const TIMER0: Self = Self(0);
+  pub const TIMER1: Self = Self(1);
+  pub const TIMER2: Self = Self(2);
+  pub const TIMER3: Self = Self(3);
+
+  /// ## Panics
+  /// * If `index` isn't `0..4`.
+  #[inline]
+  pub const fn new(index: usize) -> Self {
+    assert!(index < 4);
+    Self(index)
+  }
+  #[inline]
+  pub const fn index(self) -> usize {
+    self.0
+  }
+
+  #[inline]
+  const fn count_addr(self) -> VolAddress<u16, Safe, ()> {
+    unsafe { VolAddress::new(0x0400_0100 + self.0 * 4) }
+  }
+  #[inline]
+  const fn reload_addr(self) -> VolAddress<u16, (), Safe> {
+    unsafe { VolAddress::new(0x0400_0100 + self.0 * 4) }
+  }
+  #[inline]
+  const fn control_addr(self) -> VolAddress<TimerControl, Safe, Safe> {
+    unsafe { VolAddress::new(0x0400_0102 + self.0 * 4) }
+  }
+
+  /// Sets the value the counter restarts from after an overflow (and when the
+  /// timer is started).
+  #[inline]
+  pub fn set_reload(self, reload: u16) {
+    self.reload_addr().write(reload);
+  }
+
+  /// (Re)starts the timer from its reload value, using `control` with the
+  /// enabled bit set.
+  #[inline]
+  pub fn start(self, control: TimerControl) {
+    self.control_addr().write(TimerControl::new());
+    self.control_addr().write(control.with_enabled(true));
+  }
+
+  /// Stops the timer. The counter keeps its current value.
+  #[inline]
+  pub fn stop(self) {
+    let control = self.control_addr().read();
+    self.control_addr().write(control.with_enabled(false));
+  }
+
+  /// The current counter value.
+  #[inline]
+  pub fn read(self) -> u16 {
+    self.count_addr().read()
+  }
+
+  #[inline]
+  pub fn control(self) -> TimerControl {
+    self.control_addr().read()
+  }
+}